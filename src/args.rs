@@ -1,50 +1,583 @@
-use clap::{arg, command, Parser};
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
+use clap::parser::ValueSource;
 use std::path::PathBuf;
 use std::process;
+use std::time::Duration;
 
 /// A fast directory tree scanner, listing the top n files in the tree
 /// by size. Intended use, is to help quickly identify which files are
 /// consuming space on your drive.
-#[derive(Parser)]
+#[derive(Parser, Clone)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
     /// A valid directory path to start scanning from. Defaults to '.'
     #[arg(index = 1, value_name = "PATH", default_value = ".")]
     pub path: PathBuf,
 
+    /// Further directories to scan, each on its own, with --separate-roots.
+    #[arg(index = 2, value_name = "ROOTS", requires = "separate_roots")]
+    pub roots: Vec<PathBuf>,
+
     /// Find files >= to size (in bytes).
     #[arg(short= 's', long, value_name = "MINSIZE", default_value_t = 0)]
     pub minsize: u64,
 
-    /// number of entries to display
+    /// number of entries to display, 0 to only count files.
     #[arg(short, long, value_name = "N_ENTRIES", default_value_t = 10)]
     pub nentries: usize,
 
+    /// leave the N largest entries out of the listing, still counting them in the totals.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub trim_top: usize,
+
+    /// also list the M smallest files, after the largest.
+    #[arg(long, value_name = "M")]
+    pub bottom: Option<usize>,
+
+    /// hide listed files smaller than size (in bytes), without affecting the scan.
+    #[arg(long, value_name = "SIZE", default_value_t = 0)]
+    pub display_min: u64,
+
+    /// hide listed files smaller than this fraction, from 0 to 1, of the largest listed.
+    #[arg(long, value_name = "FRACTION", value_parser = parse_rate)]
+    pub rel_min: Option<f64>,
+
+    /// output format. 'json' writes the listing and summary as one JSON object, once
+    /// the scan completes, and 'yaml' as one YAML document. 'msgpack' streams every entry
+    /// that could be listed as it's found, not just the top n, then the summary, each as a
+    /// MessagePack map.
+    #[arg(long, value_name = "FORMAT", default_value = "table")]
+    pub format: OutputFormat,
+
+    /// Whether --format was given, rather than defaulted.
+    #[arg(skip)]
+    pub format_given: bool,
+
+    /// print only the byte size of each listed entry, one per line.
+    #[arg(long, required = false, default_value = "false", conflicts_with = "format")]
+    pub size_only: bool,
+
+    /// draw the final table in a grid of borders, sizing its columns to fit. The table
+    /// is still drawn plain while scanning.
+    #[arg(long, required = false, default_value = "false", conflicts_with = "size_only")]
+    pub borders: bool,
+
+    /// hash the listed files that share their size with another, mark each duplicate with
+    /// the file it copies, and total the space the copies take.
+    #[arg(long, required = false, default_value = "false")]
+    pub find_dupes: bool,
+
+    /// exit with status 1 when no entries are listed, as when a filter matches nothing. With
+    /// --separate-roots, when any root lists none, once every root has been scanned.
+    #[arg(long, required = false, default_value = "false")]
+    pub fail_if_empty: bool,
+
+    /// show the inode number of each listed entry, on Unix. Elsewhere there's no such column.
+    #[arg(long, required = false, default_value = "false")]
+    pub show_inode: bool,
+
+    /// show a BLAKE3 checksum of each listed file, and group listed files with identical content.
+    #[arg(long, required = false, default_value = "false")]
+    pub checksum: bool,
+
+    /// suggest the largest listed files to delete to free SIZE bytes.
+    #[arg(long, value_name = "SIZE")]
+    pub free: Option<u64>,
+
+    /// browse the final listing, deleting selected files after confirmation.
+    #[arg(long, required = false, default_value = "false", conflicts_with_all = ["format", "size_only"])]
+    pub interactive: bool,
+
+    /// with --interactive, only report the files that would be deleted, and with --open
+    /// the directory that would be opened.
+    #[arg(long, required = false, default_value = "false")]
+    pub dry_run: bool,
+
+    /// open the directory holding the largest listed file in the file manager. With
+    /// --interactive, 'o' opens the selected entry's directory instead.
+    #[arg(long, required = false, default_value = "false")]
+    pub open: bool,
+
+    /// order of the final listing: 'size', 'path', or a date, newest first. The top n
+    /// are still picked by size.
+    #[arg(long, value_name = "KEY", default_value = "size")]
+    pub display_sort: DisplaySort,
+
+    /// re-read the size of each listed file once the scan completes, dropping any that
+    /// have been deleted since.
+    #[arg(long, required = false, default_value = "false")]
+    pub verify: bool,
+
+    /// buffer size in bytes for output written once the scan completes, without a terminal.
+    #[arg(long, value_name = "BYTES", default_value_t = 64 * 1024)]
+    pub output_buffer_size: usize,
+
+    /// pretty print JSON output, instead of on a single line.
+    #[arg(long, required = false, default_value = "false")]
+    pub json_pretty: bool,
+
     /// print line numbers.
     #[arg(short, long, value_name = "INDEX", required = false, default_value = "false")]
     pub index_print: bool,
 
-    /// print size in Mb.
+    /// print size in MiB, or MB with --si.
     #[arg(short, long, value_name = "Mb", required = false, default_value = "false")]
     pub m_byt: bool,
 
-    /// print size in Gb.
+    /// print size in GiB, or GB with --si.
     #[arg(short, long, value_name = "Gb", required = false, default_value = "false")]
     pub g_byt: bool,
 
+    /// use SI units, powers of 1000 bytes: MB and GB with --m-byt or --g-byt, and kB, MB,
+    /// GB and so on wherever sizes are shown for reading.
+    #[arg(long, required = false, default_value = "false", overrides_with = "iec")]
+    pub si: bool,
+
+    /// use IEC units, powers of 1024 bytes: MiB, GiB and so on, the default. Overrides an
+    /// earlier --si, as from a shell alias.
+    #[arg(long, required = false, default_value = "false", overrides_with = "si")]
+    pub iec: bool,
+
+    /// include directories in the listing, marked with a trailing '/'.
+    #[arg(long, required = false, default_value = "false")]
+    pub include_dirs: bool,
+
+    /// write a one line JSON run summary to stderr.
+    #[arg(long, required = false, default_value = "false")]
+    pub metrics: bool,
+
+    /// only list files whose path contains SUBSTR. May be repeated.
+    #[arg(long, value_name = "SUBSTR")]
+    pub path_contains: Vec<String>,
+
+    /// match path filters case-sensitively.
+    #[arg(long, required = false, default_value = "false")]
+    pub case_sensitive: bool,
+
+    /// skip files and directories matching the glob PATTERN. Patterns without a '/'
+    /// match the base name, others the path relative to PATH. May be repeated.
+    #[arg(long, value_name = "PATTERN")]
+    pub exclude: Vec<String>,
+
+    /// skip files owned by USER, a name or numeric uid, neither counting nor listing
+    /// them. Unix only. May be repeated.
+    #[arg(long, value_name = "USER")]
+    pub exclude_owner: Vec<String>,
+
+    /// skip directories listed as mount points in /proc/mounts (Linux), other than PATH.
+    #[arg(long, required = false, default_value = "false")]
+    pub exclude_mount_points: bool,
+
+    /// only list files directly inside PATH, without descending into subdirectories.
+    #[arg(long, required = false, default_value = "false")]
+    pub no_recursion: bool,
+
+    /// leave empty directories out of the directory count.
+    #[arg(long, required = false, default_value = "false")]
+    pub skip_empty_dirs: bool,
+
+    /// print the final listing in reverse, so the first entry ends up last, nearest the
+    /// prompt. JSON, YAML and MessagePack output keep their order.
+    #[arg(long, required = false, default_value = "false")]
+    pub reverse_final: bool,
+
+    /// pin the status line to the bottom of the terminal.
+    #[arg(long, required = false, default_value = "false")]
+    pub status_bottom: bool,
+
+    /// never scroll the terminal while scanning, only the rows that fit below the cursor
+    /// are drawn live. The full listing is written once the scan completes.
+    #[arg(long, required = false, default_value = "false")]
+    pub no_scroll: bool,
+
+    /// heading printed above the table.
+    #[arg(long, default_value = "")]
+    pub title: String,
+
+    /// raise the size floor to this percentile of the file sizes seen so far, skipping
+    /// smaller files for speed. Results become approximate.
+    #[arg(long, value_name = "PERCENTILE", value_parser = clap::value_parser!(u8).range(1..100))]
+    pub adaptive_floor: Option<u8>,
+
+    /// pad the size column to WIDTH characters, for sizes too wide for the default.
+    #[arg(long, value_name = "WIDTH", default_value = "15")]
+    pub size_width: usize,
+
+    /// show only the last K components of each path, after a leading '…'.
+    #[arg(long, value_name = "K", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub path_tail: Option<usize>,
+
+    /// shorten paths longer than WIDTH characters, replacing the middle with '…'.
+    #[arg(long, value_name = "WIDTH")]
+    pub abbreviate_paths: Option<usize>,
+
+    /// cut rows to WIDTH characters, shortening the path first. Defaults to the
+    /// terminal width.
+    #[arg(long, value_name = "WIDTH")]
+    pub max_line_width: Option<usize>,
+
+    /// show a bar for each file, sized relative to the largest.
+    #[arg(long, required = false, default_value = "false")]
+    pub bars: bool,
+
+    /// colors for a 'dark' or 'light' terminal background. 'auto' goes by $COLORFGBG
+    /// where set, else dark.
+    #[arg(long, value_name = "THEME", default_value = "auto")]
+    pub theme: ThemeName,
+
+    /// don't color the output, as when $NO_COLOR is set.
+    #[arg(long, required = false, default_value = "false")]
+    pub no_color: bool,
+
+    /// show dates relative to now, e.g. '3d ago' or '2mo ago'.
+    #[arg(long, required = false, default_value = "false")]
+    pub relative_dates: bool,
+
+    /// show the listed paths as absolute paths with symlinks resolved, where they still exist.
+    #[arg(long, required = false, default_value = "false")]
+    pub canonical: bool,
+
+    /// show paths with '/' separators on platforms that use another, as on Windows.
+    #[arg(long, required = false, default_value = "false")]
+    pub forward_slashes: bool,
+
+    /// only print plain ASCII, for legacy terminals and serial consoles.
+    #[arg(long, required = false, default_value = "false")]
+    pub ascii: bool,
+
+    /// print the time spent in each phase of the scan to stderr.
+    #[arg(long, required = false, default_value = "false")]
+    pub profile: bool,
+
+    /// stop scanning once N files have been examined.
+    #[arg(long, value_name = "N")]
+    pub max_files: Option<usize>,
+
+    /// print a histogram of file sizes by order of magnitude.
+    #[arg(long, required = false, default_value = "false")]
+    pub histogram: bool,
+
+    /// also list the base file names with the largest total size, over all their copies.
+    #[arg(long, required = false, default_value = "false")]
+    pub by_name: bool,
+
+    /// break the summary down into regular files, directories, symlinks and other files,
+    /// and any errors by kind.
+    #[arg(long, required = false, default_value = "false")]
+    pub stats: bool,
+
+    /// also list the directories holding more than N entries, most first.
+    #[arg(long, value_name = "N")]
+    pub dense_dirs: Option<usize>,
+
+    /// also show the number of files and bytes at each depth below PATH.
+    #[arg(long, required = false, default_value = "false")]
+    pub by_depth: bool,
+
+    /// also list the top n files and subtotals of each filesystem the scan crossed.
+    #[arg(long, required = false, default_value = "false")]
+    pub by_volume: bool,
+
+    /// print each file that becomes the largest found so far to stderr, as it's found, or once the scan ends when stderr is the live display's terminal.
+    #[arg(long, required = false, default_value = "false")]
+    pub announce_leaders: bool,
+
+    /// retry failed directory reads and metadata lookups N times before counting an error.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub retry: u32,
+
+    /// bound the queue of directories waiting to be scanned to N, and the scans running at
+    /// once to N. A scan that finds the queue full goes on to the subdirectories itself.
+    /// Unbounded by default.
+    #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub dirs_channel_capacity: Option<usize>,
+
+    /// make at most N metadata calls a second, to go easy on a busy disk.
+    #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub max_stats_per_second: Option<usize>,
+
+    /// read at most N directories at once. Defaults to half the open file limit.
+    #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub max_open_dirs: Option<usize>,
+
+    /// stack size in bytes of the scan and printer threads, at least 1 MiB. Defaults to
+    /// 2 MiB, as for any Rust or tokio thread.
+    #[arg(long, value_name = "BYTES", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1024 * 1024..))]
+    pub stack_size: Option<usize>,
+
+    /// only examine this fraction of files, from 0 to 1, picked by a hash of their path.
+    /// Totals are extrapolated in the summary.
+    #[arg(long, value_name = "RATE", value_parser = parse_rate)]
+    pub sample_rate: Option<f64>,
+
+    /// seed for --sample-rate, the same seed picks the same files.
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "sample_rate")]
+    pub seed: u64,
+
+    /// write the running totals to stderr every SECONDS, held until the scan ends when stderr is the live display's terminal.
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub progress_log: Option<u64>,
+
+    /// list symlinks: 'follow' by their target's size, 'nofollow' by the link's own size,
+    /// 'annotate' as follow, showing the target path. By default symlinks are counted,
+    /// but not listed.
+    #[arg(long, value_name = "MODE")]
+    pub symlinks: Option<SymlinkMode>,
+
+    /// only count and list regular files, skipping symlinks and special files.
+    #[arg(long, required = false, default_value = "false", conflicts_with_all = ["only_dirs", "include_dirs", "symlinks"])]
+    pub only_files: bool,
+
+    /// only list entries modified after FILE was.
+    #[arg(long, value_name = "FILE")]
+    pub newer_than_file: Option<PathBuf>,
+
+    /// only list entries modified within DURATION, e.g. '90s', '30m', '2h' or '1d'.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub active_within: Option<Duration>,
+
+    /// skip files created within DURATION, e.g. '90s' or '30m', such as the temp files of
+    /// a build in progress. They're counted separately under --stats.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub skip_newer_than: Option<Duration>,
+
+    /// only list entries modified before FILE was.
+    #[arg(long, value_name = "FILE")]
+    pub older_than_file: Option<PathBuf>,
+
+    /// only list directories, files are still counted.
+    #[arg(long, required = false, default_value = "false")]
+    pub only_dirs: bool,
+
+    /// exit with an error if PATH is empty, e.g. a mount point with nothing mounted.
+    #[arg(long, required = false, default_value = "false")]
+    pub require_nonempty: bool,
+
+    /// count files and directories whose names aren't valid UTF-8 as errors, rather than
+    /// listing them with replacement characters.
+    #[arg(long, required = false, default_value = "false")]
+    pub strict_utf8: bool,
+
+    /// print each path that counts as an error, and why, to stderr, held until the scan ends when stderr is the live display's terminal.
+    #[arg(long, required = false, default_value = "false")]
+    pub verbose_errors: bool,
+
+    /// print each directory that took longer than MS milliseconds to list and stat to stderr, held until the scan ends when stderr is the live display's terminal.
+    #[arg(long, value_name = "MS")]
+    pub warn_slow_dirs: Option<u64>,
+
+    /// skip sockets, fifos and device files entirely, neither counting nor listing them.
+    #[arg(long, required = false, default_value = "false")]
+    pub skip_special: bool,
+
+    /// rank block devices by their capacity, other device files are never ranked.
+    #[arg(long, required = false, default_value = "false")]
+    pub block_device_sizes: bool,
+
+    /// also write the listing to FILE as a standalone HTML page, with the size histogram
+    /// under --histogram.
+    #[arg(long, value_name = "FILE")]
+    pub html: Option<PathBuf>,
+
+    /// also write the listing and summary to FILE as JSON, as --format json does. On
+    /// SIGTERM the results so far are written, then scanr exits.
+    #[arg(long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// also write the one line run summary of --metrics to FILE, once the scan completes.
+    #[arg(long, value_name = "FILE")]
+    pub summary_file: Option<PathBuf>,
+
+    /// also write the whole tree to FILE as nested JSON, each directory with its total
+    /// size, its files and its subdirectories.
+    #[arg(long, value_name = "FILE", conflicts_with = "files_from")]
+    pub tree_json: Option<PathBuf>,
+
+    /// merge the listing into a JSON index in FILE, which keeps the top n files seen
+    /// over every run, with when each was last seen.
+    #[arg(long, value_name = "FILE")]
+    pub index_file: Option<PathBuf>,
+
+    /// only list entries modified since the last run recorded in FILE, then record this
+    /// run. The first run lists everything.
+    #[arg(long, value_name = "FILE")]
+    pub state: Option<PathBuf>,
+
+    /// only list entries at least N levels below PATH, those directly in it being at level 1.
+    /// Shallower files are still counted, and directories still descended into.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub min_depth: usize,
+
+    /// only list entries matching EXPR, e.g. 'size > 100M and ext == log and mtime < -30d'.
+    /// Fields are size, name, ext, path, and mtime, ctime and atime relative to now, joined
+    /// with and, or, not and parentheses. Text is compared with ==, != or ~ (contains).
+    #[arg(long, value_name = "EXPR")]
+    pub filter: Option<String>,
+
+    /// rank only the files listed in FILE, one path per line, instead of walking PATH.
+    #[arg(long, value_name = "FILE")]
+    pub files_from: Option<PathBuf>,
+
+    /// scan PATH and each of ROOTS in turn, each with its own listing and summary under
+    /// its path as a title.
+    #[arg(long, required = false, default_value = "false", requires = "roots",
+          conflicts_with_all = ["merge", "files_from", "interactive", "output", "summary_file", "html", "tree_json", "index_file", "state"])]
+    pub separate_roots: bool,
+
+    /// rank the entries of prior --format json or --output dumps together, instead of
+    /// walking PATH.
+    #[arg(long, value_name = "FILE", num_args = 1.., conflicts_with_all = ["files_from", "tree_json"])]
+    pub merge: Vec<PathBuf>,
+
+    /// with --merge, write each path as host:path, with the host its dump was taken on.
+    #[arg(long, required = false, default_value = "false", requires = "merge")]
+    pub prefix_host: bool,
+
+    /// write every file that could be listed, not just the top n, to a Parquet FILE: those
+    /// of at least --minsize bytes that pass the filters.
+    #[cfg(feature = "parquet")]
+    #[arg(long, value_name = "FILE", conflicts_with = "separate_roots")]
+    pub dump_parquet: Option<PathBuf>,
+
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Msgpack,
+    Yaml,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum DisplaySort {
+    Size,
+    Path,
+    Created,
+    Modified,
+    Accessed,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ThemeName {
+    Dark,
+    Light,
+    Auto,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SymlinkMode {
+    Follow,
+    Nofollow,
+    Annotate,
+}
+
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (n, unit) = s.split_at(split);
+    let secs = match unit {
+        "s" | "" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(format!("unknown unit '{unit}', expected s, m, h or d")),
+    };
+    let n = n.parse::<u64>().map_err(|_| format!("{s} is not a duration"))?;
+    n.checked_mul(secs).map(Duration::from_secs).ok_or_else(|| format!("{s} is too long a duration"))
+}
+
+fn parse_rate(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate <= 1.0 => Ok(rate),
+        _ => Err(format!("{s} is not a number between 0 and 1")),
+    }
 }
 
 impl Args {
     pub fn parse_args() -> Self {
-        let args = Self::parse();
+        let matches = Self::command().get_matches();
+        let mut args = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        args.format_given = matches.value_source("format") == Some(ValueSource::CommandLine);
         args.validate();
+        if !args.case_sensitive {
+            args.path_contains = args.path_contains.iter().map(|s| s.to_lowercase()).collect();
+        }
         args
     }
     fn validate(&self) {
-        if ! self.path.is_dir() {
-            eprintln!("Error: The path {} does not exist or is not a directory.", self.path.display());
+        // a single stat, the scan opens the directory itself.
+        for path in std::iter::once(&self.path).chain(&self.roots) {
+            match std::fs::metadata(path) {
+                Ok(m) if m.is_dir() => {},
+                Ok(_) => {
+                    eprintln!("Error: The path {} is not a directory.", path.display());
+                    process::exit(2);
+                },
+                Err(e) => {
+                    eprintln!("Error: The path {} cannot be read: {e}", path.display());
+                    process::exit(2);
+                },
+            }
+        }
+        if self.dry_run && !self.interactive && !self.open {
+            Self::exit_invalid("--dry-run needs --interactive or --open");
+        }
+        if self.borders && self.format != OutputFormat::Table {
+            Self::exit_invalid("--borders only applies to --format table");
+        }
+        // only opened for --require-nonempty, and read no further than the first entry.
+        let roots = std::iter::once(&self.path).chain(&self.roots);
+        if let Some(empty) = roots.filter(|_| self.require_nonempty).find(|p| p.read_dir().is_ok_and(|mut d| d.next().is_none())) {
+            eprintln!("Error: The path {} is empty.", empty.display());
             process::exit(2);
         }
     }
 
+    pub fn list_files(&self) -> bool {
+        !self.only_dirs
+    }
+
+    pub fn list_dirs(&self) -> bool {
+        self.include_dirs || self.only_dirs
+    }
+
+    /// True when every examined file is needed, not just candidates for the top n.
+    pub fn every_file(&self) -> bool {
+        #[cfg(feature = "parquet")]
+        if self.dump_parquet.is_some() {
+            return true;
+        }
+        self.by_name || self.by_volume || self.bottom.is_some() || self.format == OutputFormat::Msgpack
+    }
+
+    /// Reports an invalid argument value and exits.
+    pub fn exit_invalid(msg: &str) -> ! {
+        crate::util::print::restore_terminal();
+        eprintln!("Error: {msg}");
+        process::exit(2);
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_reads_each_unit() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(2 * 60 * 60)));
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(24 * 60 * 60)));
+        assert!(parse_duration("1w").is_err());
+        assert!(parse_duration("d").is_err());
+    }
+
+    #[test]
+    fn parse_duration_rejects_durations_that_overflow() {
+        assert!(parse_duration("18446744073709551615s").is_ok());
+        assert!(parse_duration("18446744073709551615d").is_err());
+        assert!(parse_duration("99999999999999999999").is_err());
+    }
 }
@@ -1,62 +1,217 @@
 mod args;
 pub mod util;
 
-use crate::util::print::{display_time};
+use crate::util::print::{display_time, human_size, Status};
 use bisection::bisect_left;
-use futures::future::join_all;
 use sorted_vec::ReverseSortedVec;
 use std::cmp::{Ordering, Reverse};
+use std::fs::Metadata;
 use std::path::PathBuf;
-use std::sync::atomic::AtomicU64;
+use std::sync::atomic::{AtomicU64, AtomicUsize};
 use std::sync::atomic::Ordering::SeqCst;
 use std::sync::Arc;
-use std::{thread};
+use std::{io, thread};
+use std::io::{stdout, Write};
 use core::time::Duration;
 use std::ops::AddAssign;
-use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
-use tokio::time::Instant;
-use util::print::FilePrinter;
-use crate::args::Args;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio::sync::mpsc::{channel, unbounded_channel, Receiver, Sender, UnboundedReceiver, UnboundedSender};
+use tokio::sync::mpsc::error::TrySendError;
+use util::print::{resolve_output_mode, restore_terminal, write_json, FilePrinter, OutputMode};
+use util::bottom::Smallest;
+use util::clock::{Clock, SystemClock, Throttle};
+use util::depths::Depths;
+use util::errors::{too_many_open_files, ErrorKinds};
+use util::filter::Filter;
+use util::floor::AdaptiveFloor;
+use util::free::FreePlan;
+use util::groups::NameGroups;
+use util::tree::Tree;
+use util::volumes::Volumes;
+use util::histogram::{Chart, Histogram};
+use util::{html, index, interactive, merge, open, state};
+#[cfg(feature = "parquet")]
+use util::dump::ParquetDump;
+use util::profile::{self, Phase};
+use serde::{Deserialize, Serialize};
+use num_format::{Locale, ToFormattedString};
+use crate::args::{Args, OutputFormat, SymlinkMode};
 
 
 pub enum StatusMsg<'a> {
-    Status(&'a ScanResult),
-    Final(ScanResult, Duration),
+    Status(&'a ScanResult, u64),
+    Final(Box<ScanResult>, Duration),
 }
 
 enum StatusUpdate {
-    Result(ScanResult),
-    File(Filesize),
+    Result(Box<ScanResult>),
+    Files(Vec<Filesize>),
+    /// Asks for the current top n and counts on stderr, on SIGUSR1.
+    Snapshot,
+    /// Asks for the result line to be redrawn, sent every `HEARTBEAT` on a terminal.
+    Heartbeat,
+    /// Asks for the results so far to be written to `--output`, on SIGTERM.
+    Terminate,
+    /// Asks for the terminal to be restored before exiting, on SIGINT.
+    Interrupt,
+    /// The names and sizes of the files directly in a directory, for `--tree-json`.
+    Dir(PathBuf, Vec<(String, u64)>),
+    /// A line for stderr, printed where it won't tear through the live display.
+    Note(String),
 }
 
-impl From<PathBuf> for StatusUpdate {
-    fn from(path: PathBuf) -> Self {
-        let meta = path.metadata().unwrap();
-        StatusUpdate::File(
-            Filesize {
-                path: path.to_str().unwrap().to_string(),
-                size: path.metadata().unwrap().len(),
-                modified: display_time(meta.modified()),
-                created: display_time(meta.created()),
-                used: display_time(meta.accessed()),
-            }
-        )
+/// Max files `scan_dir` collects before sending them on as one `StatusUpdate::Files`.
+const BATCH_SIZE: usize = 1024;
+
+/// The longest the result line goes without a redraw while scanning on a terminal.
+const HEARTBEAT: Duration = Duration::from_millis(250);
+
+/// The conventional status for a process ended by SIGINT.
+const INTERRUPTED: i32 = 128 + 2;
+
+impl Filesize {
+    fn with_meta(path: PathBuf, meta: &Metadata) -> Self {
+        Filesize {
+            path: path.to_string_lossy().into_owned(),
+            fs_path: path,
+            size: meta.len(),
+            modified: display_time(meta.modified()),
+            created: display_time(meta.created()),
+            used: display_time(meta.accessed()),
+            is_dir: meta.is_dir(),
+            link_target: None,
+            is_symlink: meta.is_symlink(),
+            times: Times {
+                created: meta.created().ok(),
+                modified: meta.modified().ok(),
+                accessed: meta.accessed().ok(),
+            },
+            device: device(meta),
+            inode: inode(meta),
+            checksum: None,
+            duplicate_of: None,
+        }
+    }
+
+    /// Resolves the path for `--canonical`, keeping it as it is if that fails.
+    fn canonicalize(&mut self) {
+        if let Ok(path) = std::fs::canonicalize(self.fs_path()) {
+            self.path = path.to_string_lossy().into_owned();
+            self.fs_path = path;
+        }
+    }
+
+    /// The path to act on the file by, which unlike the shown `path` keeps names that
+    /// aren't valid UTF-8.
+    pub fn fs_path(&self) -> &std::path::Path {
+        match self.fs_path.as_os_str().is_empty() {
+            true => std::path::Path::new(&self.path),
+            false => &self.fs_path,
+        }
+    }
+
+    /// The entry to rank for a symlink, per `--symlinks`. Links to directories, and
+    /// broken links when following, are not ranked.
+    fn symlink(path: PathBuf, mode: SymlinkMode) -> Option<Self> {
+        let meta = match mode {
+            SymlinkMode::Nofollow => path.symlink_metadata(),
+            SymlinkMode::Follow | SymlinkMode::Annotate => path.metadata(),
+        };
+        let meta = profile::time(Phase::Metadata, || meta).ok().filter(|m| !m.is_dir())?;
+        let link_target = match mode {
+            SymlinkMode::Annotate => path.read_link().ok().map(|t| t.display().to_string()),
+            _ => None,
+        };
+        Some(Filesize { link_target, is_symlink: true, ..Filesize::with_meta(path, &meta) })
+    }
+
+    /// The entry to rank for a block device, sized by seeking to the end of it.
+    #[cfg(unix)]
+    fn block_device(path: PathBuf, meta: &Metadata) -> Option<Self> {
+        use std::io::{Seek, SeekFrom};
+        use std::os::unix::fs::FileTypeExt;
+        if !meta.file_type().is_block_device() {
+            return None;
+        }
+        let size = std::fs::File::open(&path).and_then(|mut f| f.seek(SeekFrom::End(0))).ok()?;
+        Some(Filesize { size, ..Filesize::with_meta(path, meta) })
+    }
+
+    #[cfg(not(unix))]
+    fn block_device(_path: PathBuf, _meta: &Metadata) -> Option<Self> {
+        None
     }
 }
 
-struct Dir {
-    path: PathBuf,
-    tx_dir: UnboundedSender<Dir>,
+#[cfg(unix)]
+fn device(meta: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.dev()
+}
+
+#[cfg(not(unix))]
+fn device(_meta: &Metadata) -> u64 {
+    0
+}
+
+#[cfg(unix)]
+fn inode(meta: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.ino()
+}
+
+#[cfg(not(unix))]
+fn inode(_meta: &Metadata) -> u64 {
+    0
+}
+
+/// The channels every `scan_dir` task reports on. They're shared rather than cloned
+/// into each queued directory, so the directory queue holds nothing but paths.
+struct Channels {
+    tx_dir: DirSender,
     tx_file: UnboundedSender<StatusUpdate>,
 }
 
-#[derive(PartialOrd, Eq, Clone)]
+#[derive(Default, Eq, Clone, Copy, PartialEq)]
+pub struct Times {
+    created: Option<std::time::SystemTime>,
+    modified: Option<std::time::SystemTime>,
+    accessed: Option<std::time::SystemTime>,
+}
+
+#[derive(Eq, Clone, Serialize, Deserialize)]
 pub struct Filesize {
+    /// Names that aren't valid UTF-8 are kept, with replacement characters.
     path: String,
+    /// The path as found, see `fs_path`. Empty for entries read back by `--merge`.
+    #[serde(skip)]
+    fs_path: PathBuf,
     size: u64,
-    modified: String,
     created: String,
+    modified: String,
+    #[serde(rename = "accessed")]
     used: String,
+    is_dir: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    link_target: Option<String>,
+    #[serde(skip)]
+    is_symlink: bool,
+    /// The times behind the dates above, for sorting and `--relative-dates`.
+    #[serde(skip)]
+    times: Times,
+    /// The filesystem the entry is on, for `--by-volume`.
+    #[serde(skip)]
+    device: u64,
+    /// Zero off Unix, for `--show-inode`.
+    #[serde(skip)]
+    inode: u64,
+    /// Only computed for the final listing, with `--checksum`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checksum: Option<String>,
+    /// The larger or earlier listed file with the same content, with `--find-dupes`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duplicate_of: Option<String>,
 }
 
 impl Ord for Filesize {
@@ -65,6 +220,12 @@ impl Ord for Filesize {
     }
 }
 
+impl PartialOrd for Filesize {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl PartialEq for Filesize {
     fn eq(&self, other: &Self) -> bool {
         self.size == other.size
@@ -72,152 +233,1166 @@ impl PartialEq for Filesize {
 }
 
 
-#[derive(Default)]
+#[derive(Default, Serialize, Deserialize)]
 pub struct ScanResult {
     errors: usize,
     files: usize,
     directories: usize,
+    bytes: u64,
+    capped: bool,
+    /// Files skipped by `--skip-newer-than`.
+    #[serde(skip)]
+    recent: usize,
+    /// Of the files, those that are symlinks, and sockets, fifos or device files, for `--stats`.
+    #[serde(skip)]
+    symlinks: usize,
+    #[serde(skip)]
+    special: usize,
+    #[serde(skip)]
+    error_kinds: ErrorKinds,
+    #[serde(skip)]
+    histogram: Histogram,
+    #[serde(skip)]
+    depths: Depths,
+    /// Directories over the `--dense-dirs` threshold, with their number of entries.
+    #[serde(skip)]
+    dense: Vec<(PathBuf, usize)>,
+}
+
+/// One line run summary, emitted to stderr with `--metrics` and written by `--summary-file`.
+#[derive(Serialize)]
+struct Metrics<'a> {
+    #[serde(flatten)]
+    result: &'a ScanResult,
+    elapsed: f64,
 }
 
 impl AddAssign for ScanResult {
     fn add_assign(&mut self, other: Self) {
         self.errors += other.errors;
+        self.error_kinds += other.error_kinds;
         self.files += other.files;
         self.directories += other.directories;
+        self.bytes += other.bytes;
+        self.capped |= other.capped;
+        self.recent += other.recent;
+        self.symlinks += other.symlinks;
+        self.special += other.special;
+        self.histogram += other.histogram;
+        self.depths += other.depths;
+        self.dense.extend(other.dense);
+    }
+}
+
+
+/// Settings and counters shared by every `scan_dir` task.
+struct Scan {
+    args: Args,
+    filter: Filter,
+    examined: AtomicUsize,
+    progress: Progress,
+    /// Permits to hold a directory open, per `--max-open-dirs`.
+    open_dirs: Semaphore,
+    /// A token bucket for metadata calls, refilled by a timer under `--max-stats-per-second`.
+    stats: Option<Semaphore>,
+}
+
+/// Running totals over every directory finished so far, for `--progress-log`.
+#[derive(Default)]
+struct Progress {
+    errors: AtomicUsize,
+    files: AtomicUsize,
+    directories: AtomicUsize,
+    bytes: AtomicU64,
+}
+
+impl Progress {
+    fn add(&self, result: &ScanResult) {
+        self.errors.fetch_add(result.errors, SeqCst);
+        self.files.fetch_add(result.files, SeqCst);
+        self.directories.fetch_add(result.directories, SeqCst);
+        self.bytes.fetch_add(result.bytes, SeqCst);
+    }
+
+    fn snapshot(&self) -> ScanResult {
+        ScanResult {
+            errors: self.errors.load(SeqCst),
+            files: self.files.load(SeqCst),
+            directories: self.directories.load(SeqCst),
+            bytes: self.bytes.load(SeqCst),
+            ..ScanResult::default()
+        }
+    }
+}
+
+impl Scan {
+    fn new(args: Args) -> Self {
+        Scan {
+            filter: Filter::new(&args),
+            examined: AtomicUsize::new(0),
+            progress: Progress::default(),
+            open_dirs: Semaphore::new(args.max_open_dirs.unwrap_or_else(default_open_dirs)),
+            stats: args.max_stats_per_second.map(Semaphore::new),
+            args,
+        }
+    }
+
+    /// Counts a file as examined, returning false once `--max-files` is reached.
+    fn examine(&self) -> bool {
+        let n = self.examined.fetch_add(1, SeqCst);
+        self.args.max_files.is_none_or(|max| n < max)
+    }
+
+    /// Runs an fs call, retrying errors that may be transient up to `--retry` times
+    /// with a doubling backoff.
+    async fn retry<T>(&self, mut f: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+        let mut backoff = Duration::from_millis(10);
+        let mut result = f();
+        for _ in 0..self.args.retry {
+            match &result {
+                Err(e) if !matches!(e.kind(), io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied) => {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    result = f();
+                },
+                _ => break,
+            }
+        }
+        result
+    }
+
+    /// Whether a file falls in the `--sample-rate` sample. The pick hashes the path
+    /// with the seed, so runs over the same tree agree.
+    fn sampled(&self, path: &std::path::Path) -> bool {
+        let Some(rate) = self.args.sample_rate else {
+            return true;
+        };
+        // FNV-1a, which unlike the std hasher is stable across Rust releases.
+        let mut hash: u64 = 0xcbf29ce484222325 ^ self.args.seed;
+        for byte in path.as_os_str().as_encoded_bytes() {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        (hash as f64) < rate * u64::MAX as f64
+    }
+
+    /// Has the printer print why `path` counts as an error, under `--verbose-errors`.
+    fn report(&self, tx_file: &UnboundedSender<StatusUpdate>, path: &std::path::Path, error: impl std::fmt::Display) {
+        if self.args.verbose_errors {
+            let _ = tx_file.send(StatusUpdate::Note(format!("{}: {error}", path.display())));
+        }
+    }
+
+    /// Waits for a token to make a metadata call, when they're rate limited.
+    async fn throttle(&self) {
+        if let Some(stats) = &self.stats {
+            stats.acquire().await.expect("stats semaphore closed").forget();
+        }
+    }
+
+    /// Tops the `--max-stats-per-second` bucket up by `tokens`, never past a second's worth.
+    fn refill(&self, tokens: usize) {
+        if let (Some(stats), Some(rate)) = (&self.stats, self.args.max_stats_per_second) {
+            stats.add_permits(tokens.min(rate.saturating_sub(stats.available_permits())));
+        }
+    }
+
+    /// Opens a directory, waiting out `EMFILE` while other scans hold the file
+    /// descriptors, and otherwise retrying as `retry` does.
+    async fn read_dir(&self, path: &std::path::Path) -> io::Result<std::fs::ReadDir> {
+        let mut backoff = Duration::from_millis(10);
+        let mut result = self.retry(|| std::fs::read_dir(path)).await;
+        for _ in 0..10 {
+            match &result {
+                Err(e) if too_many_open_files(e) => {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    result = self.retry(|| std::fs::read_dir(path)).await;
+                },
+                _ => break,
+            }
+        }
+        result
+    }
+
+    fn capped(&self) -> bool {
+        self.args.max_files.is_some_and(|max| self.examined.load(SeqCst) >= max)
+    }
+}
+
+
+/// The default for `--max-open-dirs`: half the soft open file limit, leaving the rest
+/// for stdio, the files being checksummed and so on.
+fn default_open_dirs() -> usize {
+    (open_file_limit() / 2).max(1)
+}
+
+/// The soft `RLIMIT_NOFILE`, 256 by default on macOS. An unlimited one is taken as
+/// `MAX_LIMIT`, past which more open directories gain nothing.
+#[cfg(unix)]
+fn open_file_limit() -> usize {
+    const MAX_LIMIT: libc::rlim_t = 1 << 16;
+    let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    // SAFETY: getrlimit only writes to the struct it's given.
+    match unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } {
+        0 => limit.rlim_cur.min(MAX_LIMIT) as usize,
+        _ => 256,
+    }
+}
+
+/// The C runtime's default limit on Windows.
+#[cfg(not(unix))]
+fn open_file_limit() -> usize {
+    512
+}
+
+/// The sending half of the directory channel, bounded by `--dirs-channel-capacity`.
+#[derive(Clone)]
+enum DirSender {
+    Unbounded(UnboundedSender<PathBuf>),
+    Bounded(Sender<PathBuf>),
+}
+
+enum DirReceiver {
+    Unbounded(UnboundedReceiver<PathBuf>),
+    Bounded(Receiver<PathBuf>),
+}
+
+impl DirSender {
+    /// Waits for room on a bounded channel.
+    async fn send(&self, dir: PathBuf) -> Result<(), ()> {
+        match self {
+            DirSender::Unbounded(tx) => tx.send(dir).map_err(|_| ()),
+            DirSender::Bounded(tx) => tx.send(dir).await.map_err(|_| ()),
+        }
+    }
+
+    /// Queues `dir` if there's room, handing it back if a bounded channel is full.
+    fn offer(&self, dir: PathBuf) -> Result<(), PathBuf> {
+        match self {
+            DirSender::Unbounded(tx) => tx.send(dir).map_err(|_| ()),
+            DirSender::Bounded(tx) => match tx.try_send(dir) {
+                Err(TrySendError::Full(dir)) => return Err(dir),
+                sent => sent.map_err(|_| ()),
+            },
+        }.expect("failed to send dir on channel");
+        Ok(())
+    }
+}
+
+impl DirReceiver {
+    async fn recv(&mut self) -> Option<PathBuf> {
+        match self {
+            DirReceiver::Unbounded(rx) => rx.recv().await,
+            DirReceiver::Bounded(rx) => rx.recv().await,
+        }
+    }
+
+    fn try_recv(&mut self) -> Option<PathBuf> {
+        match self {
+            DirReceiver::Unbounded(rx) => rx.try_recv().ok(),
+            DirReceiver::Bounded(rx) => rx.try_recv().ok(),
+        }
+    }
+}
+
+fn dir_channel(capacity: Option<usize>) -> (DirSender, DirReceiver) {
+    match capacity {
+        Some(capacity) => {
+            let (tx, rx) = channel(capacity);
+            (DirSender::Bounded(tx), DirReceiver::Bounded(rx))
+        },
+        None => {
+            let (tx, rx) = unbounded_channel();
+            (DirSender::Unbounded(tx), DirReceiver::Unbounded(rx))
+        },
     }
 }
 
 
+/// Scans `path`, then any of its subdirectories that found the directory queue full,
+/// and theirs, so scans never wait on each other for room in the queue.
+async fn scan_tree(
+    path: PathBuf,
+    floor: Arc<AtomicU64>,
+    scan: Arc<Scan>,
+    channels: Arc<Channels>,
+) {
+    let mut pending = vec![path];
+    while let Some(path) = pending.pop() {
+        scan_dir(path, floor.load(SeqCst), &scan, &channels, &mut pending).await;
+    }
+}
+
 async fn scan_dir(
     path: PathBuf,
     min_size: u64,
-    tx_file: UnboundedSender<StatusUpdate>,
-    tx_dir: UnboundedSender<Dir>,
+    scan: &Scan,
+    channels: &Channels,
+    pending: &mut Vec<PathBuf>,
 ) {
-    let mut errors: usize = 0;
+    let (tx_file, tx_dir) = (&channels.tx_file, &channels.tx_dir);
+    // fails once the printer has gone, when there's no one left to scan for.
+    let send = |update| profile::time(Phase::Send, || tx_file.send(update)).is_ok();
+    let mut errors = ErrorKinds::default();
     let mut files: usize = 0;
+    let mut bytes: u64 = 0;
+    let mut batch: Vec<Filesize> = vec![];
+    let mut capped = false;
+    let mut directories: usize = 1;
+    let mut histogram = Histogram::default();
+    let mut dense = vec![];
+    let mut tree: Vec<(String, u64)> = vec![];
+    let mut recent: usize = 0;
+    let (mut symlinks, mut special): (usize, usize) = (0, 0);
+    let (args, filter) = (&scan.args, &scan.filter);
+    let min_size = match args.every_file() {
+        true => args.minsize,
+        false if args.nentries == 0 => u64::MAX,
+        false => min_size,
+    };
 
-    if let Ok(dir_iter) = std::fs::read_dir(path) {
-        for r in dir_iter {
+    let permit = scan.open_dirs.acquire().await.expect("open dirs semaphore closed");
+    let opened = std::time::Instant::now();
+    let dir_iter = profile::time_async(Phase::ReadDir, scan.read_dir(&path)).await;
+    if let Ok(mut dir_iter) = dir_iter {
+        let mut children: usize = 0;
+        while let Some(r) = profile::time(Phase::ReadDir, || dir_iter.next()) {
+            children += 1;
+            if scan.capped() {
+                capped = true;
+                break;
+            }
+            if batch.len() == BATCH_SIZE && !send(StatusUpdate::Files(std::mem::take(&mut batch))) {
+                return;
+            }
+            scan.throttle().await;
             match r {
 
-                Ok(e) if e.file_type().is_ok_and(|f| f.is_symlink()) => files +=1,
+                Ok(e) if filter.excluded(&e.path()) => {},
+
+                Ok(e) if args.strict_utf8 && e.file_name().to_str().is_none() => {
+                    errors.add_other();
+                    scan.report(tx_file, &e.path(), "name is not valid UTF-8");
+                },
+
+                Ok(e) if !e.file_type().is_ok_and(|f| f.is_dir()) && !scan.sampled(&e.path()) => {},
+
+                Ok(e) if args.skip_special && e.file_type().is_ok_and(|f| !f.is_file() && !f.is_dir() && !f.is_symlink()) => {},
+
+                // special files and symlinks are skipped entirely with --only-files.
+                Ok(e) if args.only_files && !e.file_type().is_ok_and(|f| f.is_file() || f.is_dir()) => {},
+
+                Ok(e) if !e.file_type().is_ok_and(|f| f.is_dir()) && !scan.examine() => {
+                    capped = true;
+                    break;
+                },
+
+                Ok(e) if e.file_type().is_ok_and(|f| f.is_symlink()) => {
+                    files += 1;
+                    symlinks += 1;
+                    let link = args.symlinks.filter(|_| args.list_files())
+                        .and_then(|mode| Filesize::symlink(e.path(), mode));
+                    if let Some(link) = link.filter(|l| l.size >= min_size && filter.keep(&e.path())) {
+                        batch.push(link);
+                    }
+                },
+
+                Ok(e) if e.file_type().is_ok_and(|f| f.is_dir()) => {
+                    // a directory removed since it was listed is still descended into, and
+                    // counted as an error there.
+                    if args.list_dirs() {
+                        match profile::time_async(Phase::Metadata, scan.retry(|| e.metadata())).await {
+                            Ok(m) if m.len() >= min_size && filter.keep(&e.path()) && filter.keep_modified(&m)
+                                && filter.keep_matching(&e.path(), &m) => batch.push(Filesize::with_meta(e.path(), &m)),
+                            _ => {},
+                        }
+                    }
+                    if args.no_recursion {
+                        directories += 1;
+                        continue;
+                    }
+                    if let Err(dir) = profile::time(Phase::Send, || tx_dir.offer(e.path())) {
+                        pending.push(dir);
+                    }
+                },
+
+                Ok(e) => match profile::time_async(Phase::Metadata, scan.retry(|| e.metadata())).await {
+                    Ok(m) if filter.excluded_owner(&m) => {},
 
-                Ok(e) if e.file_type().is_ok_and(|f| f.is_dir()) => tx_dir.send(
-                    Dir{path: e.path(), tx_dir: tx_dir.clone(), tx_file: tx_file.clone()})
-                                .expect("failed to send dir on channel"),
+                    Ok(m) if filter.recently_created(&m) => recent += 1,
 
-                Ok(e) if e.metadata().is_ok_and(|m| m.len() >= min_size) =>
-                    tx_file.send(e.path().into()).map_or_else(
-                        |_| errors +=1, |_| files +=1),
+                    // device files, fifos and sockets report no meaningful length, so they
+                    // are counted but add nothing to the byte total or the histogram.
+                    Ok(m) if !m.is_file() => {
+                        files += 1;
+                        special += 1;
+                        let device = args.block_device_sizes.then(|| Filesize::block_device(e.path(), &m)).flatten();
+                        if let Some(device) = device.filter(|d| d.size >= min_size && args.list_files() && filter.keep(&e.path())) {
+                            batch.push(device);
+                        }
+                    },
 
-                Ok(e) if e.metadata().is_err() => errors += 1,
+                    Ok(m) if m.len() >= min_size && args.list_files() && filter.keep(&e.path()) && filter.keep_modified(&m)
+                        && filter.keep_matching(&e.path(), &m) => {
+                        bytes += m.len();
+                        histogram.add(m.len());
+                        files += 1;
+                        if args.tree_json.is_some() {
+                            tree.push((e.file_name().to_string_lossy().into_owned(), m.len()));
+                        }
+                        batch.push(Filesize::with_meta(e.path(), &m))
+                    },
+
+                    Ok(m) => {  // file loaded ok, but < the minimum size or filtered out
+                        bytes += m.len();
+                        histogram.add(m.len());
+                        files += 1;
+                        if args.tree_json.is_some() {
+                            tree.push((e.file_name().to_string_lossy().into_owned(), m.len()));
+                        }
+                    },
 
-                Ok(_) => files +=1,  // file loaded ok, but < the minimum size
+                    Err(err) => {
+                        errors.add(&err);
+                        scan.report(tx_file, &e.path(), err);
+                    },
+                },
 
-                Err(_) => errors +=1,
+                Err(err) => {
+                    errors.add(&err);
+                    scan.report(tx_file, &path, err);
+                },
             }
         };
-    } else {
-        errors += 1;
+        if children == 0 && args.skip_empty_dirs {
+            directories -= 1;
+        }
+        if args.dense_dirs.is_some_and(|n| children > n) {
+            dense.push((path.clone(), children));
+        }
+    } else if let Err(err) = dir_iter {
+        errors.add(&err);
+        scan.report(tx_file, &path, err);
     };
-    tx_file.send(StatusUpdate::Result(ScanResult { errors, files, directories: 1 })).unwrap();
+    drop(permit);
+    let took = opened.elapsed();
+    if let Some(ms) = args.warn_slow_dirs.filter(|ms| took >= Duration::from_millis(*ms)) {
+        send(StatusUpdate::Note(format!("slow directory: {} took {} ms, over {ms} ms", path.display(), took.as_millis())));
+    }
+    if !batch.is_empty() && !send(StatusUpdate::Files(batch)) {
+        return;
+    }
+    if args.tree_json.is_some() && !send(StatusUpdate::Dir(path.clone(), tree)) {
+        return;
+    }
+    let mut depths = Depths::default();
+    if args.by_depth {
+        depths.add(depth(&args.path, &path) + 1, files, bytes);
+    }
+    let result = ScanResult {
+        errors: errors.total(), error_kinds: errors,
+        files, directories, bytes, capped, recent, symlinks, special, histogram, depths, dense,
+    };
+    scan.progress.add(&result);
+    send(StatusUpdate::Result(Box::new(result)));
 }
 
 
-fn print_files(min_size: Arc<AtomicU64>, mut rx_file: UnboundedReceiver<StatusUpdate>) {
+/// How many levels `path` is below `root`, or the length of `path` outside of it.
+fn depth(root: &std::path::Path, path: &std::path::Path) -> usize {
+    path.strip_prefix(root).unwrap_or(path).components().count()
+}
 
-    let start_time = Instant::now();
+/// Sends on the entries and summaries of the dumps given to `--merge`. A dump that
+/// can't be read counts as one error.
+async fn merge_dumps(scan: Arc<Scan>, tx_file: UnboundedSender<StatusUpdate>) {
+    for path in &scan.args.merge {
+        let (files, result) = match merge::read(path, scan.args.prefix_host) {
+            Ok(dump) => dump,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                let mut result = ScanResult { errors: 1, ..Default::default() };
+                result.error_kinds.add_other();
+                (vec![], result)
+            },
+        };
+        for batch in files.chunks(BATCH_SIZE) {
+            tx_file.send(StatusUpdate::Files(batch.to_vec())).unwrap();
+        }
+        tx_file.send(StatusUpdate::Result(Box::new(result))).unwrap();
+    }
+}
 
-    let n = Args::parse_args().nentries;
-    let mut printer = FilePrinter::new("");
+/// Ranks the files named in `list`, one path per line, for `--files-from`. Paths that
+/// can't be read count as errors, and directories are skipped.
+async fn scan_list(
+    list: String,
+    floor: Arc<AtomicU64>,
+    scan: Arc<Scan>,
+    tx_file: UnboundedSender<StatusUpdate>,
+) {
+    let mut result = ScanResult::default();
+    let mut batch: Vec<Filesize> = vec![];
+    let (args, filter) = (&scan.args, &scan.filter);
 
-    let mut entries = ReverseSortedVec::<Filesize>::with_capacity(n);
+    for path in list.lines().filter(|l| !l.is_empty()).map(PathBuf::from) {
+        if batch.len() == BATCH_SIZE {
+            let batch = std::mem::take(&mut batch);
+            profile::time(Phase::Send, || tx_file.send(StatusUpdate::Files(batch))).unwrap();
+        }
+        if filter.excluded(&path) || !scan.sampled(&path) {
+            continue;
+        }
+        if args.strict_utf8 && path.to_str().is_none() {
+            result.errors += 1;
+            result.error_kinds.add_other();
+            scan.report(&tx_file, &path, "name is not valid UTF-8");
+            continue;
+        }
+        if !scan.examine() {
+            result.capped = true;
+            break;
+        }
+        scan.throttle().await;
+        let min_size = match args.every_file() {
+            true => args.minsize,
+            false if args.nentries == 0 => u64::MAX,
+            false => floor.load(SeqCst),
+        };
+        match profile::time_async(Phase::Metadata, scan.retry(|| path.metadata())).await {
+            Ok(m) if m.is_dir() || (args.skip_special && !m.is_file()) || filter.excluded_owner(&m) => {},
+            Ok(m) if filter.recently_created(&m) => result.recent += 1,
+            Ok(m) => {
+                result.files += 1;
+                result.special += !m.is_file() as usize;
+                result.bytes += m.len();
+                result.histogram.add(m.len());
+                if args.by_depth {
+                    result.depths.add(depth(&args.path, &path), 1, m.len());
+                }
+                if m.len() >= min_size && args.list_files() && filter.keep(&path) && filter.keep_modified(&m) && filter.keep_matching(&path, &m) {
+                    batch.push(Filesize::with_meta(path, &m));
+                }
+            },
+            Err(err) => {
+                result.errors += 1;
+                result.error_kinds.add(&err);
+                scan.report(&tx_file, &path, err);
+            },
+        }
+    }
+    if !batch.is_empty() {
+        profile::time(Phase::Send, || tx_file.send(StatusUpdate::Files(batch))).unwrap();
+    }
+    scan.progress.add(&result);
+    profile::time(Phase::Send, || tx_file.send(StatusUpdate::Result(Box::new(result)))).unwrap();
+}
+
+
+/// Collects and prints the scan's results, returning how many entries were listed.
+fn print_files(args: Args, mode: OutputMode, min_size: Arc<AtomicU64>, mut rx_file: UnboundedReceiver<StatusUpdate>, clock: impl Clock) -> usize {
+
+    let n = args.nentries;
+    // the `--trim-top` outliers are collected like any other entry, but never shown.
+    let trim = args.trim_top;
+    let keep = if n > 0 {n + trim} else {0};
+    let mut printer = FilePrinter::new(&args, mode);
+
+    let mut entries = ReverseSortedVec::<Filesize>::with_capacity(keep);
     let mut current_status = ScanResult::default();
+    let mut adaptive = args.adaptive_floor.map(AdaptiveFloor::new);
+    let mut by_name = args.by_name.then(NameGroups::default);
+    let mut by_volume = args.by_volume.then(|| Volumes::new(n));
+    let mut smallest = args.bottom.map(Smallest::new);
+    let mut tree = args.tree_json.is_some().then(Tree::default);
+    let mut leader: u64 = 0;
+    let mut redraw = Throttle::new(HEARTBEAT);
+    #[cfg(feature = "parquet")]
+    let mut dump = args.dump_parquet.as_ref().map(|path|
+        ParquetDump::create(path).unwrap_or_else(|e| Args::exit_invalid(&e)));
 
     while let Some(msg) = rx_file.blocking_recv() {
 
         match msg {
             StatusUpdate::Result(sr) => {
-                current_status += sr;
-                if &current_status.directories % 10 == 0 {
-                    printer.print_status(StatusMsg::Status(&current_status));
+                current_status += *sr;
+                // a directory's files are sent before its result, so they're all passed on.
+                printer.flush_stream();
+                // also redrawn after a pause, or a file-sparse walk would seem to stall between every tenth directory.
+                let due = redraw.due(&clock);
+                if current_status.directories.is_multiple_of(10) || due {
+                    redraw.mark(&clock);
+                    let msg = StatusMsg::Status(&current_status, min_size.load(SeqCst));
+                    profile::time(Phase::Render, || printer.print_status(msg));
+                    if due {
+                        printer.flush_status();
+                    }
+                }
+            },
+
+            // flushed too, or the redraw would sit queued until 20 more had been made.
+            StatusUpdate::Heartbeat => if redraw.due(&clock) {
+                redraw.mark(&clock);
+                let msg = StatusMsg::Status(&current_status, min_size.load(SeqCst));
+                profile::time(Phase::Render, || printer.print_status(msg));
+                printer.flush_status();
+            },
+
+            StatusUpdate::Dir(dir, files) => if let Some(tree) = tree.as_mut() {
+                tree.add(dir, files);
+            },
+
+            StatusUpdate::Note(msg) => printer.note(msg),
+
+            StatusUpdate::Terminate => if let Some(path) = &args.output {
+                let listed: Vec<&Filesize> = entries.iter().skip(trim).map(|e| &e.0)
+                    .filter(|e| e.size >= args.display_min).collect();
+                let written = write_json(path, &listed, &current_status, clock.elapsed());
+                printer.restore();
+                match written {
+                    Ok(()) => eprintln!("terminated, results so far written to {}", path.display()),
+                    Err(e) => eprintln!("Error: {e}"),
                 }
+                // the conventional status for a process ended by SIGTERM.
+                std::process::exit(128 + 15);
             },
 
-            StatusUpdate::File(file) => {
+            StatusUpdate::Interrupt => {
+                printer.leave();
+                printer.restore();
+                std::process::exit(INTERRUPTED);
+            },
+
+            StatusUpdate::Snapshot => {
+                let listed: Vec<&Filesize> = entries.iter().skip(trim).map(|e| &e.0)
+                    .filter(|e| e.size >= args.display_min).collect();
+                let snapshot = format!("{}{} bytes: {}", printer.render(&listed), Status(&current_status), human_size(current_status.bytes));
+                printer.note(snapshot);
+            },
+
+            StatusUpdate::Files(files) => for file in files {
+                printer.stream(&file);
+                #[cfg(feature = "parquet")]
+                if let Some(dump) = dump.as_mut() {
+                    dump.write(&file);
+                }
+                if let Some(groups) = by_name.as_mut() {
+                    groups.add(&file);
+                }
+                if let Some(volumes) = by_volume.as_mut() {
+                    volumes.add(&file);
+                }
+                if let Some(smallest) = smallest.as_mut() {
+                    smallest.add(&file);
+                }
+                if args.announce_leaders && !file.is_dir && file.size > leader {
+                    leader = file.size;
+                    printer.note(format!("largest so far: {} {}", human_size(file.size), file.path));
+                }
+                if let Some(floor) = adaptive.as_mut().and_then(|a| a.sample(file.size)) {
+                    min_size.fetch_max(floor, SeqCst);
+                }
                 let current_min = min_size.load(SeqCst);
-                if file.size > current_min {
+                // with `-n 0` files are only counted. Files at the floor are kept, as in
+                // `scan_dir`, so one exactly --minsize bytes is listed.
+                if keep > 0 && file.size >= current_min {
                     let r = Reverse(file);
                     let idx = bisect_left(&entries, &r);
-                    if idx <= n {
+                    if idx <= keep {
                         entries.insert(r);
-                        while entries.len() > n {
+                        while entries.len() > keep {
                             entries.pop();
                         }
 
-                        if entries.len() == n {
+                        if entries.len() == keep {
                             if let Some(entry) = entries.last() {
-                                min_size.store(entry.0.size, SeqCst);
+                                min_size.fetch_max(entry.0.size, SeqCst);
                             }
                         }
 
-                        let n_lines = n.min(entries.len()).min(printer.page_size);
-                        if idx <= printer.page_size {
-                            for (i, entry) in entries[idx..n_lines].iter().enumerate() {
-                                printer.print_line(&entry.0, idx + i);
+                        // a new largest entry redraws every visible line, so bars stay in scale.
+                        if idx <= trim {
+                            if let Some(entry) = entries.get(trim) {
+                                printer.scale_bars(entry.0.size);
                             }
                         }
+
+                        let n_lines = keep.min(entries.len()).min(printer.page_size + trim);
+                        if idx <= printer.page_size + trim {
+                            profile::time(Phase::Render, ||
+                                for (i, entry) in entries[idx..n_lines].iter().enumerate() {
+                                    printer.print_line(&entry.0, idx + i);
+                                }
+                            );
+                        }
                     }
                 }
             }
         }
     }
-    let end_time = Instant::now();
-    let elapsed_time = end_time - start_time;
-    printer.print_final(entries, StatusMsg::Final(current_status, elapsed_time));
+    entries.drain(..trim.min(entries.len()));
+    if args.canonical {
+        entries.mutate_vec(|entries| entries.iter_mut().for_each(|e| e.0.canonicalize()));
+    }
+    #[cfg(feature = "parquet")]
+    if let Some(dump) = dump {
+        dump.finish();
+    }
+    let elapsed_time = clock.elapsed();
+    let metrics = Metrics { result: &current_status, elapsed: elapsed_time.as_secs_f64() };
+    let metrics = serde_json::to_string(&metrics).unwrap();
+    if let Some(path) = &args.summary_file {
+        if let Err(e) = std::fs::write(path, format!("{metrics}\n")) {
+            eprintln!("Error: cannot write {}: {e}", path.display());
+        }
+    }
+    let histogram = current_status.histogram;
+    let depths = std::mem::take(&mut current_status.depths);
+    let mut dense = std::mem::take(&mut current_status.dense);
+    let (files, bytes) = (current_status.files, current_status.bytes);
+    let recent = match current_status.recent {
+        0 => "".into(),
+        n => format!(" skipped as new: {}", n.to_formatted_string(&Locale::en)),
+    };
+    let stats = args.stats.then(|| format!("regular files: {} directories: {} symlinks: {} other: {}{recent}",
+        (files - current_status.symlinks - current_status.special).to_formatted_string(&Locale::en),
+        current_status.directories.to_formatted_string(&Locale::en),
+        current_status.symlinks.to_formatted_string(&Locale::en),
+        current_status.special.to_formatted_string(&Locale::en)))
+        .map(|stats| match current_status.errors {
+            0 => stats,
+            _ => format!("{stats}\n{}", current_status.error_kinds),
+        });
+    if let Some(path) = &args.output {
+        let listed: Vec<&Filesize> = entries.iter().map(|e| &e.0).filter(|e| e.size >= args.display_min).collect();
+        if let Err(e) = write_json(path, &listed, &current_status, elapsed_time) {
+            eprintln!("Error: {e}");
+        }
+    }
+    if let Some(path) = &args.html {
+        let listed: Vec<&Filesize> = entries.iter().map(|e| &e.0).filter(|e| e.size >= args.display_min).collect();
+        let histogram = args.histogram.then_some(&histogram);
+        if let Err(e) = html::write(path, &args.title, &listed, &current_status, histogram) {
+            eprintln!("Error: {e}");
+        }
+    }
+    if let (Some(tree), Some(path)) = (tree, &args.tree_json) {
+        if let Err(e) = tree.write(path, &args.path) {
+            eprintln!("Error: {e}");
+        }
+    }
+    if let Some(path) = args.index_file.as_ref().filter(|_| n > 0) {
+        let listed: Vec<&Filesize> = entries.iter().map(|e| &e.0).collect();
+        if let Err(e) = index::merge(path, &listed, n) {
+            eprintln!("Error: {e}");
+        }
+    }
+    let found = entries.iter().filter(|e| e.0.size >= args.display_min).count();
+    let free = args.free.map(|target|
+        FreePlan::select(entries.iter().map(|e| &e.0).filter(|e| e.size >= args.display_min), target));
+    // the `--trim-top` entries are already gone, and those under --display-min aren't shown.
+    let largest = entries.iter().map(|e| &e.0).find(|e| e.size >= args.display_min).map(|e| e.fs_path().to_path_buf());
+    let browse: Option<Vec<Filesize>> = (args.interactive && mode == OutputMode::Live)
+        .then(|| entries.iter().map(|e| e.0.clone()).filter(|e| e.size >= args.display_min).collect());
+    profile::time(Phase::Render, ||
+        printer.print_final(entries, StatusMsg::Final(Box::new(current_status), elapsed_time))
+    );
+    // a short list is otherwise easy to take for a bug, rather than a strict filter.
+    if n > 0 && found < n && args.format == OutputFormat::Table && !args.size_only {
+        println!("only {found} {} matched, of the {n} asked for", if found == 1 {"file"} else {"files"});
+    }
+    if let Some(stats) = stats.filter(|_| args.format == OutputFormat::Table && !args.size_only) {
+        println!("{stats}");
+    }
+    if let Some(smallest) = smallest.filter(|_| args.format == OutputFormat::Table && !args.size_only) {
+        print!("{smallest}");
+    }
+    if args.histogram && args.format == OutputFormat::Table && !args.size_only {
+        print!("{}", Chart(&histogram, !args.ascii));
+    }
+    if args.by_depth && args.format == OutputFormat::Table && !args.size_only {
+        print!("{depths}");
+    }
+    if args.dense_dirs.is_some() && args.format == OutputFormat::Table && !args.size_only {
+        dense.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        println!("dense directories:");
+        for (dir, children) in &dense {
+            println!("  {:>12}  {}", children.to_formatted_string(&Locale::en), dir.display());
+        }
+    }
+    if let Some(groups) = by_name.filter(|_| args.format == OutputFormat::Table && !args.size_only) {
+        print!("{}", groups.top(n));
+    }
+    if let Some(volumes) = by_volume.filter(|_| args.format == OutputFormat::Table && !args.size_only) {
+        print!("{volumes}");
+    }
+    if let Some(rate) = args.sample_rate.filter(|_| args.format == OutputFormat::Table && !args.size_only) {
+        println!("sampled {:.1}% of files, totals extrapolate to about {} files and {}", rate * 100.0,
+                 ((files as f64 / rate).round() as usize).to_formatted_string(&Locale::en),
+                 human_size((bytes as f64 / rate).round() as u64));
+    }
+    if let Some(plan) = free.filter(|_| args.format == OutputFormat::Table && !args.size_only) {
+        print!("{plan}");
+        if !plan.met() {
+            eprintln!("warning: the top {n} entries can't free {}, try a larger --nentries", human_size(args.free.unwrap()));
+        }
+    }
+    if let Some(entries) = browse {
+        stdout().flush().unwrap();
+        interactive::run(entries, args.dry_run, args.open);
+    } else if args.open {
+        match (mode, largest) {
+            (OutputMode::Live, Some(path)) => match open::containing_dir(&path, args.dry_run) {
+                Ok(done) => println!("{done}"),
+                Err(e) => eprintln!("Error: {e}"),
+            },
+            (OutputMode::Live, None) => eprintln!("warning: nothing listed to --open"),
+            _ => eprintln!("warning: --open only opens a file manager from a terminal"),
+        }
+    }
+    if args.metrics {
+        eprintln!("{metrics}");
+    }
+    profile::report();
+    stdout().flush().unwrap();
+    found
 }
 
 
-#[tokio::main]
-async fn main() {
+fn main() {
+    let started = std::time::SystemTime::now();
     let args = Args::parse_args();
+    if args.si {
+        util::print::use_si_units();
+    }
+    let mut runtime = tokio::runtime::Builder::new_multi_thread();
+    runtime.enable_all();
+    if let Some(size) = args.stack_size {
+        runtime.thread_stack_size(size);
+    }
+    let runtime = runtime.build().expect("failed to start the runtime");
+    // the panic message is printed before any unwinding restores the terminal.
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        hook(info);
+    }));
+    let fail_if_empty = args.fail_if_empty;
+    let listed = match args.separate_roots {
+        false => vec![runtime.block_on(run(args, started))],
+        true => {
+            let roots: Vec<PathBuf> = std::iter::once(&args.path).chain(&args.roots).cloned().collect();
+            let mut listed = vec![];
+            for (i, root) in roots.into_iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                let mut args = args.clone();
+                args.title = match args.title.is_empty() {
+                    true => root.display().to_string(),
+                    false => format!("{} {}", args.title, root.display()),
+                };
+                args.path = root;
+                listed.push(runtime.block_on(run(args, started)));
+            }
+            listed
+        },
+    };
+    if fail_if_empty && listed.contains(&0) {
+        restore_terminal();
+        std::process::exit(1);
+    }
+}
+
+/// Scans and lists one root, returning how many entries were listed.
+async fn run(args: Args, started: std::time::SystemTime) -> usize {
+    if args.profile {
+        profile::enable();
+    }
+
+    let scan = Arc::new(Scan::new(args));
+
+    let list = scan.args.files_from.as_ref().map(|path| std::fs::read_to_string(path)
+        .unwrap_or_else(|e| Args::exit_invalid(&format!("cannot read {}: {e}", path.display()))));
 
     let file_ch = unbounded_channel::<StatusUpdate>();
 
-    let floor = Arc::new(AtomicU64::new(args.minsize));
+    let floor = Arc::new(AtomicU64::new(scan.args.minsize));
     let floor_clone = Arc::clone(&floor);
 
-    let t1 = thread::Builder::new()
-        .name("file_printer".into())
+    let mut printer = thread::Builder::new().name("file_printer".into());
+    if let Some(size) = scan.args.stack_size {
+        printer = printer.stack_size(size);
+    }
+    // resolved once, as asking the terminal for the cursor from two threads loses one of the replies.
+    let mode = resolve_output_mode(&scan.args);
+    let args = scan.args.clone();
+    let t1 = printer
         .spawn(move ||
             print_files(
+                args,
+                mode,
                 floor_clone,
-                file_ch.1
+                file_ch.1,
+                SystemClock::start(),
             )
         ).unwrap();
 
-    let init = move |path| {
-        let dir_ch = unbounded_channel::<Dir>();
-        dir_ch.0.send(
-            Dir{path, tx_dir: dir_ch.0.clone(), tx_file: file_ch.0}
-        ).unwrap();
-        dir_ch.1
+
+    let progress_log = scan.args.progress_log.map(|secs| {
+        let scan = Arc::clone(&scan);
+        let tx_file = file_ch.0.clone();
+        let clock = SystemClock::start();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(secs));
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let totals = scan.progress.snapshot();
+                let line = format!("progress: {} bytes: {} after {:.0} seconds",
+                                   Status(&totals), human_size(totals.bytes), clock.elapsed().as_secs_f64());
+                if tx_file.send(StatusUpdate::Note(line)).is_err() {
+                    break;
+                }
+            }
+        })
+    });
+
+    // the bucket is topped up ten times a second, so scans don't stall for a whole second once it's empty.
+    let refill = scan.args.max_stats_per_second.map(|rate| {
+        let scan = Arc::clone(&scan);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(100));
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                scan.refill(rate.div_ceil(10));
+            }
+        })
+    });
+
+    // keeps the result line moving while a slow directory holds up every other update.
+    let heartbeat = (mode == OutputMode::Live).then(|| {
+        let tx_file = file_ch.0.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(HEARTBEAT);
+            while tx_file.send(StatusUpdate::Heartbeat).is_ok() {
+                interval.tick().await;
+            }
+        })
+    });
+
+    #[cfg(unix)]
+    let snapshots = {
+        let tx_file = file_ch.0.clone();
+        tokio::spawn(async move {
+            use tokio::signal::unix::{signal, SignalKind};
+            let Ok(mut usr1) = signal(SignalKind::user_defined1()) else {
+                return;
+            };
+            while usr1.recv().await.is_some() && tx_file.send(StatusUpdate::Snapshot).is_ok() {}
+        })
+    };
+
+    #[cfg(unix)]
+    let terminate = scan.args.output.is_some().then(|| {
+        let tx_file = file_ch.0.clone();
+        tokio::spawn(async move {
+            use tokio::signal::unix::{signal, SignalKind};
+            if let Ok(mut term) = signal(SignalKind::terminate()) {
+                if term.recv().await.is_some() {
+                    let _ = tx_file.send(StatusUpdate::Terminate);
+                }
+            }
+        })
+    });
+
+    // the printer knows where the rows it drew end, but may be busy finishing the
+    // listing, so it only gets a moment before the terminal is restored here.
+    let interrupt = {
+        let tx_file = file_ch.0.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                let _ = tx_file.send(StatusUpdate::Interrupt);
+                tokio::time::sleep(HEARTBEAT).await;
+                restore_terminal();
+                std::process::exit(INTERRUPTED);
+            }
+        })
     };
-    let mut dir_ch = init(args.path);
 
-    let mut scans = vec![];
-    while let Some(dir) = dir_ch.recv().await {
-        scans.push(tokio::spawn(scan_dir(
-            dir.path,
-            floor.load(SeqCst),
-            dir.tx_file,
-            dir.tx_dir,
-        )));
+    let mut scans = JoinSet::new();
+    if !scan.args.merge.is_empty() {
+        scans.spawn(merge_dumps(Arc::clone(&scan), file_ch.0));
+    } else if let Some(list) = list {
+        scans.spawn(scan_list(list, Arc::clone(&floor), Arc::clone(&scan), file_ch.0));
+    } else {
+        let (tx_dir, mut rx_dir) = dir_channel(scan.args.dirs_channel_capacity);
+        let channels = Arc::new(Channels { tx_dir, tx_file: file_ch.0 });
+        channels.tx_dir.send(scan.args.path.clone()).await.unwrap();
+
+        // as many scans run at once as directories may be queued, so a bounded queue
+        // bounds the directories held open and the batches of files being collected.
+        let running = scan.args.dirs_channel_capacity.unwrap_or(usize::MAX);
+
+        // the channels stay open while held here, so the walk is done once no scan is
+        // running and none is queued: a scan queues its subdirectories before finishing.
+        loop {
+            if scans.is_empty() {
+                match rx_dir.try_recv() {
+                    Some(path) => {
+                        scans.spawn(scan_tree(path, Arc::clone(&floor), Arc::clone(&scan), Arc::clone(&channels)));
+                        continue;
+                    },
+                    None => break,
+                }
+            }
+            tokio::select! {
+                Some(path) = rx_dir.recv(), if scans.len() < running => {
+                    scans.spawn(scan_tree(path, Arc::clone(&floor), Arc::clone(&scan), Arc::clone(&channels)));
+                },
+                Some(_) = scans.join_next() => {},
+            }
+        }
+    }
+
+    while scans.join_next().await.is_some() {}
+    for task in progress_log.into_iter().chain(refill).chain(heartbeat).chain([interrupt]) {
+        task.abort();
     }
+    #[cfg(unix)]
+    for task in std::iter::once(snapshots).chain(terminate) {
+        task.abort();
+    }
+
+    let found = t1.join().unwrap();
+
+    if let Some(path) = &scan.args.state {
+        if let Err(e) = state::record(path, started) {
+            eprintln!("Error: {e}");
+        }
+    }
+    found
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::path::Path;
+    use util::clock::FixedClock;
+
+    /// A fresh directory under the system temp dir, removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("scanr-{name}-{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        fn file(&self, name: &str, size: usize) -> &Self {
+            std::fs::write(self.0.join(name), vec![0u8; size]).unwrap();
+            self
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn args(root: &Path, flags: &[&str]) -> Args {
+        Args::parse_from(["scanr", root.to_str().unwrap()].iter().chain(flags))
+    }
+
+    /// Walks `root` with `flags` as `run` does, one directory at a time, returning the
+    /// files sent on for ranking and the totals.
+    fn scan(root: &Path, flags: &[&str]) -> (Vec<Filesize>, ScanResult) {
+        let scan = Arc::new(Scan::new(args(root, flags)));
+        let (tx_dir, mut rx_dir) = dir_channel(None);
+        let (tx_file, mut rx_file) = unbounded_channel();
+        let channels = Arc::new(Channels { tx_dir, tx_file });
+        let floor = Arc::new(AtomicU64::new(scan.args.minsize));
+        let walk = async {
+            let mut next = Some(root.to_path_buf());
+            while let Some(dir) = next {
+                scan_tree(dir, Arc::clone(&floor), Arc::clone(&scan), Arc::clone(&channels)).await;
+                next = rx_dir.try_recv();
+            }
+        };
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async { tokio::time::timeout(Duration::from_secs(10), walk).await }).expect("the scan blocked");
+        drop(channels);
+
+        let (mut files, mut result) = (vec![], ScanResult::default());
+        while let Ok(update) = rx_file.try_recv() {
+            match update {
+                StatusUpdate::Files(batch) => files.extend(batch),
+                StatusUpdate::Result(r) => result += *r,
+                _ => {},
+            }
+        }
+        (files, result)
+    }
+
+    /// Lists `files` as `print_files` would after a scan with `flags` and a floor of
+    /// `floor`, returning how many entries were listed.
+    fn list(files: Vec<Filesize>, flags: &[&str], floor: u64) -> usize {
+        let (tx_file, rx_file) = unbounded_channel();
+        tx_file.send(StatusUpdate::Files(files)).unwrap();
+        tx_file.send(StatusUpdate::Result(Box::default())).unwrap();
+        drop(tx_file);
+        let args = args(Path::new("."), flags);
+        print_files(args, OutputMode::Json { pretty: false }, Arc::new(AtomicU64::new(floor)), rx_file, FixedClock::default())
+    }
+
+    #[test]
+    fn zero_entries_counts_files_without_listing_any() {
+        let dir = TempDir::new("zero-entries");
+        dir.file("a", 10).file("b", 20);
+        let (files, result) = scan(&dir.0, &["-n", "0"]);
+        assert!(files.is_empty());
+        assert_eq!((result.files, result.bytes), (2, 30));
+
+        let (files, _) = scan(&dir.0, &[]);
+        assert_eq!(files.len(), 2);
+        assert_eq!(list(files, &["-n", "0"], 0), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fifos_are_counted_as_special_without_being_opened() {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
 
-    join_all(scans).await;
+        let dir = TempDir::new("fifo");
+        dir.file("a", 10);
+        let fifo = CString::new(dir.0.join("pipe").as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(fifo.as_ptr(), 0o600) }, 0);
 
-    t1.join().unwrap();
+        // a scan that opened the fifo would block here until a writer showed up.
+        let (files, result) = scan(&dir.0, &[]);
+        assert_eq!(files.iter().map(|f| f.fs_path().file_name().unwrap()).collect::<Vec<_>>(), ["a"]);
+        assert_eq!((result.files, result.special, result.bytes), (2, 1, 10));
 
+        let (_, result) = scan(&dir.0, &["--skip-special"]);
+        assert_eq!((result.files, result.special), (1, 0));
+    }
+
+    #[test]
+    fn files_exactly_at_the_floor_are_kept() {
+        let dir = TempDir::new("floor");
+        dir.file("at", 100).file("below", 99);
+        let (files, result) = scan(&dir.0, &["-s", "100"]);
+        assert_eq!(files.iter().map(|f| f.size).collect::<Vec<_>>(), [100]);
+        assert_eq!(result.files, 2);
+
+        assert_eq!(list(files, &["-n", "1"], 100), 1);
+    }
 }
@@ -0,0 +1,42 @@
+use std::collections::BinaryHeap;
+use std::fmt::{Display, Formatter};
+use crate::Filesize;
+use crate::util::print::human_size;
+
+
+/// The smallest listed files, for `--bottom`. Kept in a max heap so the largest of
+/// them is the one to give way.
+pub struct Smallest {
+    n: usize,
+    files: BinaryHeap<Filesize>,
+}
+
+impl Smallest {
+    pub fn new(n: usize) -> Self {
+        Smallest { n, files: BinaryHeap::with_capacity(n + 1) }
+    }
+
+    pub fn add(&mut self, file: &Filesize) {
+        if file.is_dir || self.n == 0 {
+            return;
+        }
+        if self.files.len() < self.n {
+            self.files.push(file.clone());
+        } else if self.files.peek().is_some_and(|largest| file.size < largest.size) {
+            self.files.pop();
+            self.files.push(file.clone());
+        }
+    }
+}
+
+impl Display for Smallest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut files: Vec<_> = self.files.iter().collect();
+        files.sort_by(|a, b| a.size.cmp(&b.size).then_with(|| a.path.cmp(&b.path)));
+        writeln!(f, "smallest files:")?;
+        for file in files {
+            writeln!(f, "  {:>10}  {}", human_size(file.size), file.path)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::path::Path;
+use crate::Filesize;
+use crate::util::print::human_size;
+
+
+/// The BLAKE3 hash of a file's content, as hex. None if it couldn't be read.
+pub fn checksum(path: &Path) -> Option<String> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(File::open(path).ok()?).ok()?;
+    Some(hasher.finalize().to_hex().to_string())
+}
+
+/// Hashes the files in `entries` that could have a duplicate among them, that is those
+/// sharing their size with another, and marks each copy with the first file it repeats.
+pub fn mark_duplicates(entries: &mut [Filesize]) {
+    let mut sizes: HashMap<u64, usize> = HashMap::new();
+    for entry in entries.iter().filter(|e| !e.is_dir) {
+        *sizes.entry(entry.size).or_default() += 1;
+    }
+    let mut originals: HashMap<String, String> = HashMap::new();
+    for entry in entries.iter_mut().filter(|e| !e.is_dir && sizes[&e.size] > 1) {
+        if entry.checksum.is_none() {
+            entry.checksum = checksum(entry.fs_path());
+        }
+        let Some(hash) = entry.checksum.clone() else {
+            continue;
+        };
+        match originals.get(&hash) {
+            Some(original) => entry.duplicate_of = Some(original.clone()),
+            None => {
+                originals.insert(hash, entry.path.clone());
+            },
+        }
+    }
+}
+
+/// Listed files with identical content, for `--checksum` and `--find-dupes`. Groups are in the order
+/// of their largest file.
+pub struct Duplicates<'a>(Vec<Vec<&'a Filesize>>);
+
+impl<'a> Duplicates<'a> {
+    pub fn find(entries: &[&'a Filesize]) -> Self {
+        let mut order: Vec<&str> = vec![];
+        let mut groups: HashMap<&str, Vec<&Filesize>> = HashMap::new();
+        for entry in entries {
+            if let Some(hash) = entry.checksum.as_deref() {
+                let group = groups.entry(hash).or_default();
+                if group.is_empty() {
+                    order.push(hash);
+                }
+                group.push(entry);
+            }
+        }
+        Duplicates(order.iter().filter_map(|h| groups.remove(h)).filter(|g| g.len() > 1).collect())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The space taken by every copy but one of each group.
+    pub fn reclaimable(&self) -> u64 {
+        self.0.iter().map(|g| g[0].size * (g.len() as u64 - 1)).sum()
+    }
+}
+
+impl Display for Duplicates<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "duplicate files:")?;
+        for group in &self.0 {
+            writeln!(f, "  {:>10}  {}", human_size(group[0].size), &group[0].checksum.as_deref().unwrap_or("")[..16])?;
+            for file in group {
+                writeln!(f, "              {}", file.path)?;
+            }
+        }
+        Ok(())
+    }
+}
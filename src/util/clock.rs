@@ -0,0 +1,85 @@
+use core::time::Duration;
+use tokio::time::Instant;
+
+
+/// Time source for the elapsed time reported in the final summary. Lets the
+/// summary be rendered against a fixed duration instead of the wall clock.
+pub trait Clock {
+    fn elapsed(&self) -> Duration;
+}
+
+/// Wall clock time since the clock was started.
+pub struct SystemClock(Instant);
+
+impl SystemClock {
+    pub fn start() -> Self {
+        Self(Instant::now())
+    }
+}
+
+impl Clock for SystemClock {
+    fn elapsed(&self) -> Duration {
+        Instant::now() - self.0
+    }
+}
+
+/// A clock that only moves when advanced, for tests.
+#[cfg(test)]
+#[derive(Default)]
+pub struct FixedClock(std::cell::Cell<Duration>);
+
+#[cfg(test)]
+impl FixedClock {
+    pub fn advance(&self, by: Duration) {
+        self.0.set(self.0.get() + by);
+    }
+}
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn elapsed(&self) -> Duration {
+        self.0.get()
+    }
+}
+
+/// Tells when a redraw is due, `every` after the last one.
+pub struct Throttle {
+    every: Duration,
+    last: Duration,
+}
+
+impl Throttle {
+    pub fn new(every: Duration) -> Self {
+        Self { every, last: Duration::ZERO }
+    }
+
+    pub fn due(&self, clock: &impl Clock) -> bool {
+        clock.elapsed() - self.last >= self.every
+    }
+
+    /// Records a redraw, whether or not it was due.
+    pub fn mark(&mut self, clock: &impl Clock) {
+        self.last = clock.elapsed();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttle_is_due_once_the_interval_has_passed_since_the_last_mark() {
+        let clock = FixedClock::default();
+        let mut throttle = Throttle::new(Duration::from_millis(250));
+        assert!(!throttle.due(&clock));
+        clock.advance(Duration::from_millis(249));
+        assert!(!throttle.due(&clock));
+        clock.advance(Duration::from_millis(1));
+        assert!(throttle.due(&clock));
+        throttle.mark(&clock);
+        assert!(!throttle.due(&clock));
+        clock.advance(Duration::from_millis(300));
+        assert!(throttle.due(&clock));
+    }
+}
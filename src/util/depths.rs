@@ -0,0 +1,42 @@
+use std::fmt::{Display, Formatter};
+use std::ops::AddAssign;
+use num_format::{Locale, ToFormattedString};
+use crate::util::print::human_size;
+
+
+/// File counts and total bytes by depth below the root, for `--by-depth`. Files
+/// directly in the root are at depth 1.
+#[derive(Default)]
+pub struct Depths {
+    counts: Vec<usize>,
+    bytes: Vec<u64>,
+}
+
+impl Depths {
+    pub fn add(&mut self, depth: usize, files: usize, bytes: u64) {
+        if self.counts.len() <= depth {
+            self.counts.resize(depth + 1, 0);
+            self.bytes.resize(depth + 1, 0);
+        }
+        self.counts[depth] += files;
+        self.bytes[depth] += bytes;
+    }
+}
+
+impl AddAssign for Depths {
+    fn add_assign(&mut self, other: Self) {
+        for (depth, (files, bytes)) in other.counts.into_iter().zip(other.bytes).enumerate() {
+            self.add(depth, files, bytes);
+        }
+    }
+}
+
+impl Display for Depths {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "by depth:")?;
+        for (depth, (files, bytes)) in self.counts.iter().zip(&self.bytes).enumerate().filter(|(_, (files, _))| **files > 0) {
+            writeln!(f, "  {depth:>5} {:>12}  {:>10}", files.to_formatted_string(&Locale::en), human_size(*bytes))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use crate::Filesize;
+
+
+/// Streams every file sent for ranking to a Parquet file, for `--dump-parquet`.
+pub struct ParquetDump {
+    writer: ArrowWriter<File>,
+    schema: Arc<Schema>,
+    path: Vec<String>,
+    size: Vec<u64>,
+    created: Vec<String>,
+    modified: Vec<String>,
+    accessed: Vec<String>,
+}
+
+impl ParquetDump {
+    /// Rows are buffered and written in batches of this many.
+    const BATCH_ROWS: usize = 8192;
+
+    pub fn create(path: &Path) -> Result<Self, String> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("path", DataType::Utf8, false),
+            Field::new("size", DataType::UInt64, false),
+            Field::new("created", DataType::Utf8, false),
+            Field::new("modified", DataType::Utf8, false),
+            Field::new("accessed", DataType::Utf8, false),
+        ]));
+        let file = File::create(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let writer = ArrowWriter::try_new(file, Arc::clone(&schema), None).map_err(|e| e.to_string())?;
+        Ok(Self {
+            writer,
+            schema,
+            path: vec![],
+            size: vec![],
+            created: vec![],
+            modified: vec![],
+            accessed: vec![],
+        })
+    }
+
+    pub fn write(&mut self, file: &Filesize) {
+        self.path.push(file.path.clone());
+        self.size.push(file.size);
+        self.created.push(file.created.clone());
+        self.modified.push(file.modified.clone());
+        self.accessed.push(file.used.clone());
+        if self.path.len() == Self::BATCH_ROWS {
+            self.write_batch();
+        }
+    }
+
+    pub fn finish(mut self) {
+        self.write_batch();
+        self.writer.close().expect("failed to finish parquet dump");
+    }
+
+    fn write_batch(&mut self) {
+        if self.path.is_empty() {
+            return;
+        }
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(std::mem::take(&mut self.path))),
+            Arc::new(UInt64Array::from(std::mem::take(&mut self.size))),
+            Arc::new(StringArray::from(std::mem::take(&mut self.created))),
+            Arc::new(StringArray::from(std::mem::take(&mut self.modified))),
+            Arc::new(StringArray::from(std::mem::take(&mut self.accessed))),
+        ];
+        let batch = RecordBatch::try_new(Arc::clone(&self.schema), columns).unwrap();
+        self.writer.write(&batch).expect("failed to write parquet dump");
+    }
+}
@@ -0,0 +1,66 @@
+use std::fmt::{Display, Formatter};
+use std::io;
+use num_format::{Locale, ToFormattedString};
+
+
+/// Errors counted by kind, shown with `--stats`.
+#[derive(Default, Clone, Copy)]
+pub struct ErrorKinds {
+    permission_denied: usize,
+    not_found: usize,
+    too_many_open_files: usize,
+    other: usize,
+}
+
+impl ErrorKinds {
+    pub fn add(&mut self, error: &io::Error) {
+        match error.kind() {
+            io::ErrorKind::PermissionDenied => self.permission_denied += 1,
+            io::ErrorKind::NotFound => self.not_found += 1,
+            _ if too_many_open_files(error) => self.too_many_open_files += 1,
+            _ => self.other += 1,
+        }
+    }
+
+    /// Counts an error that isn't from the filesystem, such as a name rejected by `--strict-utf8`.
+    pub fn add_other(&mut self) {
+        self.other += 1;
+    }
+
+    pub fn total(&self) -> usize {
+        self.permission_denied + self.not_found + self.too_many_open_files + self.other
+    }
+}
+
+impl std::ops::AddAssign for ErrorKinds {
+    fn add_assign(&mut self, other: Self) {
+        self.permission_denied += other.permission_denied;
+        self.not_found += other.not_found;
+        self.too_many_open_files += other.too_many_open_files;
+        self.other += other.other;
+    }
+}
+
+impl Display for ErrorKinds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "errors: permission denied: {} not found: {} too many open files: {} other: {}",
+               self.permission_denied.to_formatted_string(&Locale::en),
+               self.not_found.to_formatted_string(&Locale::en),
+               self.too_many_open_files.to_formatted_string(&Locale::en),
+               self.other.to_formatted_string(&Locale::en),
+        )
+    }
+}
+
+/// Whether an error is `EMFILE`, the per process limit on open files, which has no
+/// `io::ErrorKind` of its own.
+#[cfg(unix)]
+pub fn too_many_open_files(e: &io::Error) -> bool {
+    const EMFILE: i32 = 24;
+    e.raw_os_error() == Some(EMFILE)
+}
+
+#[cfg(not(unix))]
+pub fn too_many_open_files(_e: &io::Error) -> bool {
+    false
+}
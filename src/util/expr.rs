@@ -0,0 +1,331 @@
+use std::fs::Metadata;
+use std::path::Path;
+use std::time::SystemTime;
+use crate::args::parse_duration;
+
+
+/// A `--filter` expression, checked against each candidate entry, for example
+/// `size > 100M and ext == log and mtime < -30d`.
+///
+/// Comparisons are joined with `and`, `or` and `not`, in that order of precedence
+/// from loosest, and grouped with parentheses. The fields are:
+/// - `size`, compared with `==`, `!=`, `<`, `<=`, `>` or `>=` to a number of bytes,
+///   with an optional K, M, G or T suffix, in powers of 1024
+/// - `name`, `ext` and `path`, compared with `==`, `!=` or `~` (contains) to a word
+///   or a quoted string
+/// - `mtime`, `ctime` and `atime`, compared like `size` to a time relative to now,
+///   e.g. `-30d` for 30 days ago, in s, m, h or d
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Size(Op, u64),
+    Text(Text, Op, String),
+    Time(Time, Op, SystemTime),
+}
+
+#[derive(Clone, Copy)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+#[derive(Clone, Copy)]
+pub enum Text {
+    Name,
+    Ext,
+    Path,
+}
+
+#[derive(Clone, Copy)]
+pub enum Time {
+    Modified,
+    Created,
+    Accessed,
+}
+
+impl Expr {
+    /// Parses `source`, with times taken relative to `now`. Unless `case_sensitive`, text
+    /// is compared ignoring case, as `--path-contains` is.
+    pub fn parse(source: &str, now: SystemTime, case_sensitive: bool) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, next: 0, now, case_sensitive };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(format!("unexpected '{token}' in --filter")),
+        }
+    }
+
+    pub fn matches(&self, path: &Path, meta: &Metadata, case_sensitive: bool) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(path, meta, case_sensitive) && b.matches(path, meta, case_sensitive),
+            Expr::Or(a, b) => a.matches(path, meta, case_sensitive) || b.matches(path, meta, case_sensitive),
+            Expr::Not(a) => !a.matches(path, meta, case_sensitive),
+            Expr::Size(op, size) => op.compare(&meta.len(), size),
+            Expr::Text(field, op, value) => {
+                let text = match field {
+                    Text::Name => path.file_name().map(|n| n.to_string_lossy()),
+                    Text::Ext => path.extension().map(|e| e.to_string_lossy()),
+                    Text::Path => Some(path.to_string_lossy()),
+                }.unwrap_or_default();
+                let text = match case_sensitive {
+                    true => text,
+                    false => text.to_lowercase().into(),
+                };
+                match op {
+                    Op::Contains => text.contains(value.as_str()),
+                    op => op.compare(&*text, value.as_str()),
+                }
+            },
+            // entries without the time, like creation times on some filesystems, never match.
+            Expr::Time(field, op, time) => match field {
+                Time::Modified => meta.modified(),
+                Time::Created => meta.created(),
+                Time::Accessed => meta.accessed(),
+            }.is_ok_and(|t| op.compare(&t, time)),
+        }
+    }
+}
+
+impl Op {
+    fn compare<T: PartialOrd + ?Sized>(self, a: &T, b: &T) -> bool {
+        match self {
+            Op::Eq => a == b,
+            Op::Ne => a != b,
+            Op::Lt => a < b,
+            Op::Le => a <= b,
+            Op::Gt => a > b,
+            Op::Ge => a >= b,
+            Op::Contains => false,
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    next: usize,
+    now: SystemTime,
+    case_sensitive: bool,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.next).map(String::as_str)
+    }
+
+    fn take(&mut self, expected: &str) -> Result<String, String> {
+        let token = self.tokens.get(self.next).cloned()
+            .ok_or_else(|| format!("--filter ends where {expected} was expected"))?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        let found = self.peek().is_some_and(|t| t.eq_ignore_ascii_case(word));
+        self.next += found as usize;
+        found
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while self.keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.keyword("(") {
+            let expr = self.or()?;
+            return match self.take("')'")?.as_str() {
+                ")" => Ok(expr),
+                token => Err(format!("expected ')' in --filter, found '{token}'")),
+            };
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let field = self.take("a field")?;
+        let op = match self.take("a comparison")?.as_str() {
+            "==" | "=" => Op::Eq,
+            "!=" => Op::Ne,
+            "<" => Op::Lt,
+            "<=" => Op::Le,
+            ">" => Op::Gt,
+            ">=" => Op::Ge,
+            "~" => Op::Contains,
+            token => return Err(format!("expected a comparison after {field} in --filter, found '{token}'")),
+        };
+        let value = self.take("a value")?;
+        let value = value.strip_prefix('"').map_or(value.as_str(), |v| v.strip_suffix('"').unwrap_or(v));
+        let text = |which| match op {
+            Op::Eq | Op::Ne | Op::Contains => {
+                let value = match which {
+                    Text::Ext => value.trim_start_matches('.'),
+                    _ => value,
+                };
+                Ok(Expr::Text(which, op, if self.case_sensitive {value.into()} else {value.to_lowercase()}))
+            },
+            _ => Err(format!("{field} is compared with ==, != or ~ in --filter")),
+        };
+        let time = |which| match op {
+            Op::Contains => Err(format!("{field} is compared with ==, !=, <, <=, > or >= in --filter")),
+            _ => parse_duration(value.trim_start_matches('-'))
+                .map_err(|e| format!("{e} in --filter"))
+                .and_then(|ago| self.now.checked_sub(ago)
+                    .ok_or_else(|| format!("{value} reaches further back than the system clock in --filter")))
+                .map(|time| Expr::Time(which, op, time)),
+        };
+        match field.to_ascii_lowercase().as_str() {
+            "size" => match op {
+                Op::Contains => Err(format!("{field} is compared with ==, !=, <, <=, > or >= in --filter")),
+                _ => parse_size(value).map(|size| Expr::Size(op, size)),
+            },
+            "name" => text(Text::Name),
+            "ext" => text(Text::Ext),
+            "path" => text(Text::Path),
+            "mtime" => time(Time::Modified),
+            "ctime" => time(Time::Created),
+            "atime" => time(Time::Accessed),
+            _ => Err(format!("unknown field '{field}' in --filter, expected size, name, ext, path, mtime, ctime or atime")),
+        }
+    }
+}
+
+/// A number of bytes, with an optional K, M, G or T suffix, and optionally a trailing B.
+fn parse_size(s: &str) -> Result<u64, String> {
+    let digits = s.trim_end_matches(['b', 'B']);
+    let split = digits.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(digits.len());
+    let (n, unit) = digits.split_at(split);
+    let power = match unit.to_ascii_uppercase().as_str() {
+        "" => 0,
+        "K" => 1,
+        "M" => 2,
+        "G" => 3,
+        "T" => 4,
+        _ => return Err(format!("unknown size unit '{unit}' in --filter, expected K, M, G or T")),
+    };
+    n.parse::<f64>().map(|n| (n * 1024f64.powi(power)) as u64).map_err(|_| format!("{s} is not a size in --filter"))
+}
+
+/// Splits `source` into words, quoted strings, parentheses and comparison operators.
+fn tokenize(source: &str) -> Result<Vec<String>, String> {
+    let mut tokens = vec![];
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            },
+            '(' | ')' | '~' => tokens.push(chars.next().unwrap().to_string()),
+            '=' | '!' | '<' | '>' => {
+                let mut op = chars.next().unwrap().to_string();
+                if chars.next_if_eq(&'=').is_some() {
+                    op.push('=');
+                }
+                tokens.push(op);
+            },
+            '"' => {
+                let mut quoted = chars.next().unwrap().to_string();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => quoted.push(c),
+                        None => return Err("unterminated string in --filter".into()),
+                    }
+                }
+                quoted.push('"');
+                tokens.push(quoted);
+            },
+            _ => {
+                let mut word = String::new();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"()~=!<>\"".contains(*c)) {
+                    word.push(c);
+                }
+                tokens.push(word);
+            },
+        }
+    }
+    Ok(tokens)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Whether `filter` matches `path`, given the metadata of a 2 KiB file modified now.
+    fn matches(filter: &str, path: &str) -> bool {
+        let file = std::env::temp_dir().join(format!("scanr-expr-{}", std::process::id()));
+        std::fs::write(&file, vec![0u8; 2048]).unwrap();
+        let meta = file.metadata().unwrap();
+        std::fs::remove_file(&file).unwrap();
+        let expr = Expr::parse(filter, SystemTime::now() + Duration::from_secs(1), false).unwrap();
+        expr.matches(Path::new(path), &meta, false)
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or_and_not_tighter_than_and() {
+        assert!(matches("ext == log or size > 1M and name == x", "a.log"));
+        assert!(!matches("ext == txt or size > 1M and name == a.log", "a.log"));
+        assert!(matches("not ext == txt and size == 2K", "a.log"));
+        assert!(!matches("not ext == log and size == 2K", "a.log"));
+    }
+
+    #[test]
+    fn parentheses_group() {
+        assert!(!matches("(ext == log or size > 1M) and name == x", "a.log"));
+        assert!(matches("not (ext == txt or size > 1M)", "a.log"));
+    }
+
+    #[test]
+    fn sizes_take_binary_suffixes() {
+        assert_eq!(parse_size("2K"), Ok(2048));
+        assert_eq!(parse_size("1.5mb"), Ok(1536 * 1024));
+        assert_eq!(parse_size("1T"), Ok(1 << 40));
+        assert!(parse_size("1X").is_err());
+        assert!(matches("size >= 2KB and size < 2.5K", "a"));
+    }
+
+    #[test]
+    fn tilde_matches_a_substring_ignoring_case() {
+        assert!(matches("path ~ \"Logs/\"", "/var/logs/a.log"));
+        assert!(!matches("name ~ logs", "/var/logs/a.log"));
+        assert!(matches("mtime > -1h and mtime <= 0s", "a"));
+    }
+
+    #[test]
+    fn malformed_filters_are_errors() {
+        for filter in ["name == \"a", "(size > 1", "owner == me", "size ~ 1", "name < a", "size 1",
+                       "size > 1 size"] {
+            assert!(Expr::parse(filter, SystemTime::now(), false).is_err(), "{filter}");
+        }
+    }
+
+    #[test]
+    fn a_time_before_the_system_clock_can_reach_is_an_error() {
+        let Err(e) = Expr::parse("mtime < -18446744073709551615s", SystemTime::now(), false) else {
+            panic!("parsed a time before the clock's range");
+        };
+        assert!(e.contains("further back than the system clock"), "{e}");
+    }
+}
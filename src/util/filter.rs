@@ -0,0 +1,222 @@
+use std::collections::HashSet;
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use crate::args::Args;
+use crate::util::expr::Expr;
+use crate::util::state;
+
+
+/// The path filters given on the command line, applied by `scan_dir`.
+pub struct Filter {
+    root: PathBuf,
+    path_contains: Vec<String>,
+    min_depth: usize,
+    case_sensitive: bool,
+    exclude_names: GlobSet,
+    exclude_paths: GlobSet,
+    mount_points: HashSet<PathBuf>,
+    /// The files scanr itself writes, like `--output` and `--state`, which are never scanned.
+    own_files: HashSet<PathBuf>,
+    newer_than: Option<SystemTime>,
+    older_than: Option<SystemTime>,
+    excluded_owners: HashSet<u32>,
+    created_after: Option<SystemTime>,
+    expr: Option<Expr>,
+}
+
+impl Filter {
+    pub fn new(args: &Args) -> Self {
+        // like gitignore, bare patterns match the base name anywhere in the tree, while
+        // patterns containing a '/' match the path relative to the root.
+        let mut names = GlobSetBuilder::new();
+        let mut paths = GlobSetBuilder::new();
+        for pattern in &args.exclude {
+            let (set, pattern) = match pattern.contains('/') {
+                true => (&mut paths, pattern.trim_start_matches('/')),
+                false => (&mut names, pattern.as_str()),
+            };
+            let glob = GlobBuilder::new(pattern)
+                .case_insensitive(!args.case_sensitive)
+                .literal_separator(true)
+                .build()
+                .unwrap_or_else(|e| Args::exit_invalid(&e.to_string()));
+            set.add(glob);
+        }
+
+        Self {
+            root: args.path.clone(),
+            path_contains: args.path_contains.clone(),
+            min_depth: args.min_depth,
+            case_sensitive: args.case_sensitive,
+            exclude_names: names.build().unwrap(),
+            exclude_paths: paths.build().unwrap(),
+            mount_points: match args.exclude_mount_points {
+                true => mount_points(&args.path),
+                false => HashSet::new(),
+            },
+            own_files: own_files(args),
+            newer_than: args.newer_than_file.as_deref().map(modified)
+                .into_iter().chain(args.active_within.map(|d| ago(d, "--active-within")))
+                .chain(args.state.as_deref().and_then(state::last_run)).max(),
+            older_than: args.older_than_file.as_deref().map(modified),
+            excluded_owners: args.exclude_owner.iter().map(|user| uid(user)).collect(),
+            created_after: args.skip_newer_than.map(|d| ago(d, "--skip-newer-than")),
+            expr: args.filter.as_deref().map(|e| Expr::parse(e, SystemTime::now(), args.case_sensitive)
+                .unwrap_or_else(|e| Args::exit_invalid(&e))),
+        }
+    }
+
+    /// Checks a candidate path against the path filters. Entries that fail are
+    /// still counted, but never ranked.
+    pub fn keep(&self, path: &Path) -> bool {
+        self.path_contains(path) && self.deep_enough(path)
+    }
+
+    /// Checks a candidate's modified time against `--newer-than-file`,
+    /// `--active-within`, `--state` and `--older-than-file`. Like `keep`, entries that fail are still counted.
+    pub fn keep_modified(&self, meta: &Metadata) -> bool {
+        if self.newer_than.is_none() && self.older_than.is_none() {
+            return true;
+        }
+        meta.modified().is_ok_and(|t|
+            self.newer_than.is_none_or(|r| t > r) && self.older_than.is_none_or(|r| t < r))
+    }
+
+    /// Checks a candidate against the `--filter` expression. Like `keep`, entries that
+    /// fail are still counted.
+    pub fn keep_matching(&self, path: &Path, meta: &Metadata) -> bool {
+        self.expr.as_ref().is_none_or(|e| e.matches(path, meta, self.case_sensitive))
+    }
+
+    /// Excluded entries are neither counted nor ranked, and excluded directories
+    /// are not descended into.
+    pub fn excluded(&self, path: &Path) -> bool {
+        self.mount_points.contains(path)
+            || self.own_files.contains(path)
+            || path.file_name().is_some_and(|name| self.exclude_names.is_match(name))
+            || path.strip_prefix(&self.root).is_ok_and(|rel| self.exclude_paths.is_match(rel))
+    }
+
+    /// Whether a file was created within `--skip-newer-than`. Files without a
+    /// creation time are never skipped.
+    pub fn recently_created(&self, meta: &Metadata) -> bool {
+        self.created_after.is_some_and(|after| meta.created().is_ok_and(|t| t > after))
+    }
+
+    /// Whether a file is owned by an `--exclude-owner` user. Like `excluded` entries,
+    /// these are neither counted nor ranked.
+    #[cfg(unix)]
+    pub fn excluded_owner(&self, meta: &Metadata) -> bool {
+        use std::os::unix::fs::MetadataExt;
+        self.excluded_owners.contains(&meta.uid())
+    }
+
+    #[cfg(not(unix))]
+    pub fn excluded_owner(&self, _meta: &Metadata) -> bool {
+        false
+    }
+
+    /// Entries directly in the root are at depth 1, as `--by-depth` counts them.
+    fn deep_enough(&self, path: &Path) -> bool {
+        self.min_depth <= 1 || path.strip_prefix(&self.root).unwrap_or(path).components().count() >= self.min_depth
+    }
+
+    fn path_contains(&self, path: &Path) -> bool {
+        if self.path_contains.is_empty() {
+            return true;
+        }
+        let path = path.to_string_lossy();
+        let path = match self.case_sensitive {
+            true => path,
+            false => path.to_lowercase().into(),
+        };
+        self.path_contains.iter().any(|s| path.contains(s.as_str()))
+    }
+}
+
+
+/// The files written by `--output`, `--state` and the like that fall inside the tree,
+/// as they'll be seen during the scan. They may not exist yet, so only their
+/// directories are resolved.
+fn own_files(args: &Args) -> HashSet<PathBuf> {
+    let Ok(root) = args.path.canonicalize() else {
+        return HashSet::new();
+    };
+    let written = [&args.output, &args.state, &args.summary_file, &args.html, &args.tree_json, &args.index_file].into_iter();
+    #[cfg(feature = "parquet")]
+    let written = written.chain([&args.dump_parquet]);
+    written.flatten()
+        .filter_map(|path| {
+            let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            let file = dir.canonicalize().ok()?.join(path.file_name()?);
+            file.strip_prefix(&root).ok().map(|r| args.path.join(r))
+        })
+        .collect()
+}
+
+/// The uid of `user`, given as a number or a name looked up in /etc/passwd.
+#[cfg(unix)]
+fn uid(user: &str) -> u32 {
+    if let Ok(uid) = user.parse() {
+        return uid;
+    }
+    fs::read_to_string("/etc/passwd").unwrap_or_default().lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields.len() > 2 && fields[0] == user)
+        .and_then(|fields| fields[2].parse().ok())
+        .unwrap_or_else(|| Args::exit_invalid(&format!("unknown user '{user}'")))
+}
+
+#[cfg(not(unix))]
+fn uid(_user: &str) -> u32 {
+    Args::exit_invalid("--exclude-owner is only supported on Unix")
+}
+
+/// The time `d` before now, for a duration given to `arg`.
+fn ago(d: Duration, arg: &str) -> SystemTime {
+    SystemTime::now().checked_sub(d)
+        .unwrap_or_else(|| Args::exit_invalid(&format!("{arg} reaches further back than the system clock")))
+}
+
+/// The modified time of a reference file, read once at startup.
+fn modified(path: &Path) -> SystemTime {
+    fs::metadata(path).and_then(|m| m.modified())
+        .unwrap_or_else(|e| Args::exit_invalid(&format!("cannot read {}: {e}", path.display())))
+}
+
+
+/// The mount points listed in /proc/mounts below `root`, as they'll be seen during the
+/// scan, i.e. joined onto `root` as given. Empty where /proc/mounts doesn't exist.
+fn mount_points(root: &Path) -> HashSet<PathBuf> {
+    let (Ok(mounts), Ok(canonical)) = (fs::read_to_string("/proc/mounts"), root.canonicalize()) else {
+        return HashSet::new();
+    };
+    mounts.lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(|m| PathBuf::from(unescape_mount(m)))
+        .filter_map(|m| m.strip_prefix(&canonical).ok().filter(|r| !r.as_os_str().is_empty()).map(|r| root.join(r)))
+        .collect()
+}
+
+/// /proc/mounts escapes spaces, tabs, newlines and backslashes in paths as octal, e.g. '\040'.
+pub fn unescape_mount(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(i) = rest.find('\\') {
+        out.push_str(&rest[..i]);
+        match rest.get(i + 1..i + 4).and_then(|o| u8::from_str_radix(o, 8).ok()) {
+            Some(c) => {
+                out.push(c as char);
+                rest = &rest[i + 4..];
+            },
+            None => {
+                out.push('\\');
+                rest = &rest[i + 1..];
+            },
+        }
+    }
+    out.push_str(rest);
+    out
+}
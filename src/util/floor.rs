@@ -0,0 +1,42 @@
+/// Tracks the sizes of files seen so far in power of two buckets, so a size floor can
+/// be raised to an approximate percentile of them without keeping every size around.
+pub struct AdaptiveFloor {
+    percentile: u8,
+    buckets: [u64; 65],
+    seen: u64,
+}
+
+impl AdaptiveFloor {
+    /// The floor is only recomputed once per this many samples.
+    const INTERVAL: u64 = 1000;
+
+    pub fn new(percentile: u8) -> Self {
+        Self { percentile, buckets: [0; 65], seen: 0 }
+    }
+
+    /// Records a file size, returning a new floor when one is due.
+    pub fn sample(&mut self, size: u64) -> Option<u64> {
+        self.buckets[(u64::BITS - size.leading_zeros()) as usize] += 1;
+        self.seen += 1;
+        match self.seen.is_multiple_of(Self::INTERVAL) {
+            true => Some(self.floor()),
+            false => None,
+        }
+    }
+
+    /// The lower bound of the bucket holding the percentile.
+    fn floor(&self) -> u64 {
+        let target = self.seen * self.percentile as u64 / 100;
+        let mut count = 0;
+        for (i, n) in self.buckets.iter().enumerate() {
+            count += n;
+            if count > target {
+                return match i {
+                    0 => 0,
+                    _ => 1 << (i - 1),
+                };
+            }
+        }
+        0
+    }
+}
@@ -0,0 +1,42 @@
+use std::fmt::{Display, Formatter};
+use crate::Filesize;
+use crate::util::print::human_size;
+
+
+/// The listed files to delete to free a target amount of space, for `--free`. Files
+/// are picked largest first until their sizes add up to the target.
+pub struct FreePlan {
+    target: u64,
+    total: u64,
+    files: Vec<Filesize>,
+}
+
+impl FreePlan {
+    /// Picks from `entries`, which are in descending size order. Directories are
+    /// never picked.
+    pub fn select<'a>(entries: impl Iterator<Item = &'a Filesize>, target: u64) -> Self {
+        let mut plan = FreePlan { target, total: 0, files: vec![] };
+        for entry in entries.filter(|e| !e.is_dir) {
+            if plan.met() {
+                break;
+            }
+            plan.total += entry.size;
+            plan.files.push(entry.clone());
+        }
+        plan
+    }
+
+    pub fn met(&self) -> bool {
+        self.total >= self.target
+    }
+}
+
+impl Display for FreePlan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "delete these to free {}:", human_size(self.target))?;
+        for file in &self.files {
+            writeln!(f, "  {:>10}  {}", human_size(file.size), file.path)?;
+        }
+        writeln!(f, "  {:>10}  total", human_size(self.total))
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use num_format::{Locale, ToFormattedString};
+use crate::Filesize;
+use crate::util::print::human_size;
+
+
+/// Files grouped by base name, for `--by-name`. Surfaces many copies of the same
+/// file spread across the tree.
+#[derive(Default)]
+pub struct NameGroups {
+    groups: HashMap<String, (usize, u64)>,
+}
+
+impl NameGroups {
+    pub fn add(&mut self, file: &Filesize) {
+        if file.is_dir {
+            return;
+        }
+        let name = Path::new(&file.path).file_name()
+            .map_or_else(|| file.path.clone(), |n| n.to_string_lossy().into_owned());
+        let group = self.groups.entry(name).or_default();
+        group.0 += 1;
+        group.1 += file.size;
+    }
+
+    /// The `n` groups with the largest total size.
+    pub fn top(&self, n: usize) -> TopGroups<'_> {
+        let mut top: Vec<_> = self.groups.iter().collect();
+        top.sort_by(|a, b| b.1.1.cmp(&a.1.1).then_with(|| a.0.cmp(b.0)));
+        top.truncate(n);
+        TopGroups(top)
+    }
+}
+
+pub struct TopGroups<'a>(Vec<(&'a String, &'a (usize, u64))>);
+
+impl Display for TopGroups<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "largest files by name:")?;
+        writeln!(f, "  {:>10}  {:>10}  name", "total", "count")?;
+        for (name, (count, total)) in &self.0 {
+            writeln!(f, "  {:>10}  {:>10}  {name}", human_size(*total), count.to_formatted_string(&Locale::en))?;
+        }
+        Ok(())
+    }
+}
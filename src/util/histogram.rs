@@ -0,0 +1,56 @@
+use std::fmt::{Display, Formatter};
+use std::ops::AddAssign;
+use num_format::{Locale, ToFormattedString};
+use crate::util::print::human_size;
+
+
+/// File counts and total bytes by order of magnitude, in powers of 1024.
+#[derive(Default, Clone, Copy)]
+pub struct Histogram {
+    counts: [usize; Histogram::BUCKETS],
+    bytes: [u64; Histogram::BUCKETS],
+}
+
+impl Histogram {
+    const BUCKETS: usize = 5;
+    const BAR_WIDTH: usize = 30;
+
+    pub fn add(&mut self, size: u64) {
+        let bucket = match size {
+            0 => 0,
+            _ => (size.ilog2() / 10) as usize,
+        }.min(Self::BUCKETS - 1);
+        self.counts[bucket] += 1;
+        self.bytes[bucket] += size;
+    }
+}
+
+impl AddAssign for Histogram {
+    fn add_assign(&mut self, other: Self) {
+        for i in 0..Self::BUCKETS {
+            self.counts[i] += other.counts[i];
+            self.bytes[i] += other.bytes[i];
+        }
+    }
+}
+
+/// Renders a histogram as a bar chart, in plain ASCII unless `unicode`.
+pub struct Chart<'a>(pub &'a Histogram, pub bool);
+
+impl Display for Chart<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (histogram, unicode) = (self.0, self.1);
+        const LABELS: [&str; Histogram::BUCKETS] = ["< 1 KiB", "1 KiB - 1 MiB", "1 MiB - 1 GiB", "1 GiB - 1 TiB", ">= 1 TiB"];
+        let max = histogram.counts.iter().copied().max().unwrap_or(0).max(1);
+
+        writeln!(f, "size histogram:")?;
+        for ((label, count), bytes) in LABELS.iter().zip(histogram.counts).zip(histogram.bytes) {
+            let bar = (if unicode {"█"} else {"#"}).repeat(count * Histogram::BAR_WIDTH / max);
+            writeln!(f, "  {label:<13} {:>12}  {:>10}  {bar}",
+                     count.to_formatted_string(&Locale::en),
+                     human_size(bytes),
+            )?;
+        }
+        Ok(())
+    }
+}
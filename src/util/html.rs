@@ -0,0 +1,61 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use crate::{Filesize, ScanResult};
+use crate::util::histogram::{Chart, Histogram};
+use crate::util::print::{human_size, Status};
+
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em}\
+table{border-collapse:collapse}\
+th,td{padding:.2em .8em;text-align:left}\
+th{cursor:pointer;border-bottom:2px solid #888}\
+tr:nth-child(even){background:#f2f2f2}\
+td.size{text-align:right}";
+
+/// Clicking a heading sorts by that column, by the cells' data-key where present.
+const SCRIPT: &str = "document.querySelectorAll('th').forEach((th,i)=>th.onclick=()=>{\
+const body=th.closest('table').tBodies[0],rows=[...body.rows],up=th.dataset.up!=='1';\
+const key=r=>{const c=r.cells[i];return c.dataset.key!==undefined?+c.dataset.key:c.textContent};\
+rows.sort((a,b)=>(key(a)>key(b)?1:key(a)<key(b)?-1:0)*(up?1:-1));\
+th.dataset.up=up?'1':'0';rows.forEach(r=>body.appendChild(r))});";
+
+/// Writes the listing as a standalone HTML page for `--html`, with the size histogram
+/// below it when given.
+pub fn write(path: &Path, title: &str, entries: &[&Filesize], result: &ScanResult, histogram: Option<&Histogram>) -> Result<(), String> {
+    let title = match title.is_empty() {
+        true => "scanr report",
+        false => title,
+    };
+    let mut html = String::new();
+    write!(html, "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{}</title><style>{STYLE}</style></head><body>\n",
+           escape(title)).unwrap();
+    write!(html, "<h1>{}</h1>\n<p>{}, {}</p>\n", escape(title), Status(result), human_size(result.bytes)).unwrap();
+    html.push_str("<table><thead><tr><th>size</th><th>created</th><th>modified</th><th>accessed</th><th>path</th></tr></thead><tbody>\n");
+    for entry in entries {
+        writeln!(html, "<tr><td class=\"size\" data-key=\"{}\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}{}</td></tr>",
+               entry.size, human_size(entry.size), entry.created, entry.modified, entry.used,
+               escape(&entry.path), if entry.is_dir {"/"} else {""}).unwrap();
+    }
+    html.push_str("</tbody></table>\n");
+    if let Some(histogram) = histogram {
+        writeln!(html, "<pre>{}</pre>", escape(&Chart(histogram, true).to_string())).unwrap();
+    }
+    writeln!(html, "<script>{SCRIPT}</script>\n</body></html>").unwrap();
+    fs::write(path, html).map_err(|e| format!("cannot write {}: {e}", path.display()))
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
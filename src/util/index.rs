@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use crate::Filesize;
+
+
+/// One file in a `--index-file`, with when a scan last listed it.
+#[derive(Serialize, Deserialize)]
+struct IndexEntry {
+    path: String,
+    size: u64,
+    last_seen: String,
+}
+
+/// Merges the listed `entries` into the JSON index at `path`, keeping the `n` largest
+/// files seen over every run. A file listed again takes its current size.
+pub fn merge(path: &Path, entries: &[&Filesize], n: usize) -> Result<(), String> {
+    let mut index: HashMap<String, IndexEntry> = match fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str::<Vec<IndexEntry>>(&json)
+            .map_err(|e| format!("cannot parse {}: {e}", path.display()))?
+            .into_iter().map(|e| (e.path.clone(), e)).collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+        Err(e) => return Err(format!("cannot read {}: {e}", path.display())),
+    };
+
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    for entry in entries.iter().filter(|e| !e.is_dir) {
+        index.insert(entry.path.clone(), IndexEntry { path: entry.path.clone(), size: entry.size, last_seen: now.clone() });
+    }
+
+    let mut index: Vec<IndexEntry> = index.into_values().collect();
+    index.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    index.truncate(n);
+    fs::write(path, serde_json::to_string_pretty(&index).unwrap())
+        .map_err(|e| format!("cannot write {}: {e}", path.display()))
+}
@@ -0,0 +1,135 @@
+use std::fs;
+use std::io::{stdout, Write};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use crate::Filesize;
+use crate::util::open;
+use crate::util::print::human_size;
+
+
+/// Browses the final listing after the scan, for `--interactive`. Arrow keys (or j/k)
+/// move the selection, 'd' deletes the selected file once confirmed with 'y', 'o'
+/// opens its directory when `open` is set, and 'q' or Esc quits. With `dry_run`
+/// nothing is deleted or opened, and what would have been is reported instead.
+pub fn run(mut entries: Vec<Filesize>, dry_run: bool, open: bool) {
+    let mut deleted: Vec<String> = vec![];
+    let mut failed: Vec<String> = vec![];
+    let mut message: Option<String> = None;
+    let mut selected = 0;
+    let mut top = 0;
+
+    terminal::enable_raw_mode().expect("Failed to enable raw mode");
+    execute!(stdout(), EnterAlternateScreen, Hide).unwrap();
+    let _screen = Screen;
+
+    loop {
+        let rows = terminal::size().map_or(24, |s| s.1) as usize;
+        let page = rows.saturating_sub(2).max(1);
+        if selected < top {
+            top = selected;
+        } else if selected >= top + page {
+            top = selected + 1 - page;
+        }
+        draw(&entries, selected, top, page, open, message.take());
+
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind != KeyEventKind::Release => key,
+            Ok(_) => continue,
+            Err(_) => break,
+        };
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+            KeyCode::Up | KeyCode::Char('k') => selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => selected = (selected + 1).min(entries.len().saturating_sub(1)),
+            KeyCode::Char('o') if open && !entries.is_empty() => {
+                message = Some(open::containing_dir(entries[selected].fs_path(), dry_run).unwrap_or_else(|e| e));
+            },
+            KeyCode::Char('d') | KeyCode::Delete if !entries.is_empty() => {
+                let entry = &entries[selected];
+                if entry.is_dir {
+                    message = Some(format!("{} is a directory, only files are deleted", entry.path));
+                    continue;
+                }
+                if !confirm(entry, dry_run, rows) {
+                    continue;
+                }
+                match dry_run {
+                    true => deleted.push(entry.path.clone()),
+                    false => match fs::remove_file(entry.fs_path()) {
+                        Ok(()) => deleted.push(entry.path.clone()),
+                        Err(e) => {
+                            let msg = format!("failed to delete {}: {e}", entry.path);
+                            message = Some(msg.clone());
+                            failed.push(msg);
+                            continue;
+                        },
+                    },
+                }
+                entries.remove(selected);
+                selected = selected.min(entries.len().saturating_sub(1));
+            },
+            _ => {},
+        }
+    }
+    drop(_screen);
+
+    let verb = if dry_run {"would delete"} else {"deleted"};
+    for path in &deleted {
+        println!("{verb} {path}");
+    }
+    for msg in &failed {
+        eprintln!("{msg}");
+    }
+}
+
+fn draw(entries: &[Filesize], selected: usize, top: usize, page: usize, open: bool, message: Option<String>) {
+    let mut out = stdout();
+    let keys = match open {
+        true => "up/down to select, d to delete, o to open its directory, q to quit",
+        false => "up/down to select, d to delete, q to quit",
+    };
+    queue!(out, MoveTo(0, 0), Clear(ClearType::All), Print(keys)).unwrap();
+    for (i, entry) in entries.iter().enumerate().skip(top).take(page) {
+        let line = format!("{:>10}  {}{}", human_size(entry.size), entry.path, if entry.is_dir {"/"} else {""});
+        queue!(out, MoveTo(0, (i - top + 1) as u16)).unwrap();
+        match i == selected {
+            true => queue!(out, SetAttribute(Attribute::Reverse), Print(line), SetAttribute(Attribute::Reset)),
+            false => queue!(out, Print(line)),
+        }.unwrap();
+    }
+    if let Some(message) = message {
+        queue!(out, MoveTo(0, (page + 1) as u16), Print(message)).unwrap();
+    }
+    out.flush().unwrap();
+}
+
+/// Asks on the bottom line whether to delete `entry`, only 'y' confirms.
+fn confirm(entry: &Filesize, dry_run: bool, rows: usize) -> bool {
+    let prompt = match dry_run {
+        true => format!("delete {} (dry run)? [y/N] ", entry.path),
+        false => format!("delete {}? [y/N] ", entry.path),
+    };
+    execute!(stdout(), MoveTo(0, rows.saturating_sub(1) as u16), Clear(ClearType::CurrentLine), Print(prompt)).unwrap();
+    loop {
+        match event::read() {
+            Ok(Event::Key(KeyEvent { code, kind, .. })) if kind != KeyEventKind::Release =>
+                return code == KeyCode::Char('y'),
+            Ok(_) => continue,
+            Err(_) => return false,
+        }
+    }
+}
+
+/// Restores the main screen and cooked mode, however the browser exits.
+struct Screen;
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
@@ -0,0 +1,36 @@
+use std::fs;
+use std::path::Path;
+use serde::Deserialize;
+use crate::{Filesize, ScanResult};
+
+
+/// A prior `--format json` or `--output` dump, as read back by `--merge`.
+#[derive(Deserialize)]
+struct Dump {
+    entries: Vec<Filesize>,
+    summary: ScanResult,
+    #[serde(default)]
+    host: String,
+}
+
+/// Reads the listing and summary from the JSON dump at `path`. With `prefix_host` each
+/// path is written as host:path, for dumps that name the machine they were taken on.
+pub fn read(path: &Path, prefix_host: bool) -> Result<(Vec<Filesize>, ScanResult), String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    let mut dump: Dump = serde_json::from_str(&json).map_err(|e| format!("cannot parse {}: {e}", path.display()))?;
+    if prefix_host && !dump.host.is_empty() {
+        for entry in dump.entries.iter_mut() {
+            entry.path = format!("{}:{}", dump.host, entry.path);
+        }
+    }
+    Ok((dump.entries, dump.summary))
+}
+
+/// The name of this machine, or empty where it can't be found.
+pub fn hostname() -> String {
+    ["/proc/sys/kernel/hostname", "/etc/hostname"].iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .map(|name| name.trim().to_string())
+        .unwrap_or_default()
+}
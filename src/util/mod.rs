@@ -1 +1,23 @@
+pub mod bottom;
+pub mod checksum;
+pub mod clock;
+pub mod depths;
+#[cfg(feature = "parquet")]
+pub mod dump;
+pub mod errors;
+pub mod expr;
+pub mod filter;
+pub mod floor;
+pub mod free;
+pub mod groups;
+pub mod histogram;
+pub mod html;
+pub mod index;
+pub mod interactive;
+pub mod merge;
+pub mod open;
 pub mod print;
+pub mod profile;
+pub mod state;
+pub mod tree;
+pub mod volumes;
@@ -0,0 +1,18 @@
+use std::path::{Path, PathBuf};
+
+
+/// Opens the directory holding `path` in the system file manager, for `--open`. With
+/// `dry_run` nothing is opened. Either way, returns what was done.
+pub fn containing_dir(path: &Path, dry_run: bool) -> Result<String, String> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        Some(_) => Path::new("."),
+        None => path,
+    };
+    let dir: PathBuf = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    if dry_run {
+        return Ok(format!("would open {}", dir.display()));
+    }
+    opener::open(&dir).map_err(|e| format!("cannot open {}: {e}", dir.display()))?;
+    Ok(format!("opened {}", dir.display()))
+}
@@ -1,26 +1,39 @@
-use std::fmt::{Display, Formatter};
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::fmt::{Display, Formatter, Write as _};
 use std::io;
-use crossterm::cursor::{position, MoveTo};
+use crossterm::cursor::{position, MoveTo, MoveToColumn};
 use crossterm::terminal::{Clear, ClearType, ScrollUp};
-use crossterm::{execute, style::Print, style::{Attribute, Color, ResetColor, SetAttribute, SetForegroundColor}, terminal, queue};
+use crossterm::{execute, style::Print, style::{Attribute, Color, ResetColor, SetAttribute, SetForegroundColor, Stylize}, terminal, queue};
 use num_format::{Locale, ToFormattedString};
-use std::io::{stdout, Write};
-use std::time::{SystemTime};
+use std::io::{stdout, BufWriter, IsTerminal, Write};
+use std::path::{Path, MAIN_SEPARATOR};
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::Relaxed;
+use std::time::{Duration, SystemTime};
 use chrono::{DateTime, Utc};
+use comfy_table::{presets, CellAlignment, ContentArrangement, Table};
 use sorted_vec::ReverseSortedVec;
-use crate::{Filesize, ScanResult, StatusMsg};
-use crate::args::Args;
+use serde::Serialize;
+use crate::{Filesize, Metrics, ScanResult, StatusMsg};
+use crate::args::{Args, DisplaySort, OutputFormat, ThemeName};
+use crate::util::checksum::{checksum, mark_duplicates, Duplicates};
+use crate::util::merge;
 
 
-struct Status<'a>(&'a ScanResult);
+pub struct Status<'a>(pub &'a ScanResult);
 
 impl<'a> Display for Status<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let errors: String = match self.0.errors > 0 {
-            true => format!("errors: {}", self.0.errors.to_formatted_string(&Locale::en)),
+            true => format!(" errors: {}", self.0.errors.to_formatted_string(&Locale::en)),
             false => "".into(),
         };
-        write!(f, "scanned files: {} directories: {} {errors}",
+        let capped = match self.0.capped {
+            true => " (capped by --max-files)",
+            false => "",
+        };
+        write!(f, "scanned files: {} directories: {}{errors}{capped}",
                self.0.files.to_formatted_string(&Locale::en),
                self.0.directories.to_formatted_string(&Locale::en),
         )
@@ -28,138 +41,959 @@ impl<'a> Display for Status<'a> {
 }
 
 
-struct FileFormat<'a>(&'a Filesize, f64);
+/// Column options shared by every printed row.
+#[derive(Clone)]
+struct RowFormat {
+    size_factor: f64,
+    size_heading: &'static str,
+    /// The size column is padded to this many characters, per `--size-width`.
+    size_width: usize,
+    path_width: Option<usize>,
+    /// Paths are cut to their last components, per `--path-tail`.
+    path_tail: Option<usize>,
+    bars: bool,
+    bar_max: u64,
+    unicode: bool,
+    inode: bool,
+    checksum: bool,
+    /// Only the size and path columns, see `OutputMode::Flat`.
+    flat: bool,
+    forward_slashes: bool,
+    /// Dates are shown relative to this time, under `--relative-dates`.
+    relative_to: Option<SystemTime>,
+    /// Colors for directory and symlink paths, only set on a live terminal.
+    path_colors: Option<Theme>,
+    /// Rows are cut to this many characters, not counting the index.
+    line_width: Option<usize>,
+}
+
+impl RowFormat {
+    const BAR_WIDTH: usize = 10;
+
+    /// `size` in the unit of `--gb` or `--mb`, or in bytes with thousands separators.
+    fn size(&self, size: u64) -> String {
+        match self.size_factor == 1.0 {
+            true => size.to_formatted_string(&Locale::en),
+            _ => format!("{:.3}", (size as f64) / self.size_factor),
+        }
+    }
+
+    /// The created, modified and accessed dates of `entry`, as shown.
+    fn dates(&self, entry: &Filesize) -> [String; 3] {
+        let times = entry.times;
+        match self.relative_to {
+            Some(now) => [times.created, times.modified, times.accessed].map(|t| relative_time(t, now)),
+            None => [entry.created.clone(), entry.modified.clone(), entry.used.clone()],
+        }
+    }
+
+    /// `path` cut to its last `--path-tail` components.
+    fn tail<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        let start = self.path_tail.and_then(|k| path.rmatch_indices(MAIN_SEPARATOR).nth(k - 1)).map(|m| m.0);
+        match start {
+            Some(i) if i > 0 => format!("{}{}", ellipsis(self.unicode), &path[i..]).into(),
+            _ => path.into(),
+        }
+    }
+
+    /// `path` with '/' separators under `--forward-slashes`.
+    fn separators<'a>(&self, path: Cow<'a, str>) -> Cow<'a, str> {
+        match self.forward_slashes && MAIN_SEPARATOR != '/' {
+            true => path.replace(MAIN_SEPARATOR, "/").into(),
+            false => path,
+        }
+    }
+
+    /// A fixed width bar, its length proportional to `size` relative to `bar_max`.
+    fn bar(&self, size: u64) -> String {
+        const PARTS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+        let eighths = match self.bar_max {
+            0 => 0,
+            max => (size as f64 / max as f64 * (Self::BAR_WIDTH * 8) as f64).round() as usize,
+        }.min(Self::BAR_WIDTH * 8);
+
+        let bar = match self.unicode {
+            true => {
+                let mut bar = "█".repeat(eighths / 8);
+                if eighths % 8 > 0 {
+                    bar.push(PARTS[eighths % 8]);
+                }
+                bar
+            },
+            false => "#".repeat((eighths + 4) / 8),
+        };
+        format!("{bar:<width$}  ", width = Self::BAR_WIDTH)
+    }
+}
+
+struct FileFormat<'a>(&'a Filesize, &'a RowFormat);
 impl<'a> Display for FileFormat<'a>{
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 
-        let size_str = match self.1 == 1.0 {
-            true => self.0.size.to_formatted_string(&Locale::en),
-            _ => format!("{:.3}", (self.0.size as f64) / self.1),
+        let size_str = self.1.size(self.0.size);
+
+        let dir_mark = match self.0.is_dir {
+            true => "/",
+            false => "",
         };
 
-        write!(f, "{size_str:>15}  {:>10}  {:>10}  {:>10}  {}",
-               self.0.created, self.0.modified, self.0.used, self.0.path
-        )
+
+        let bar = match self.1.bars {
+            true => self.1.bar(self.0.size),
+            false => "".into(),
+        };
+
+        let link = match &self.0.link_target {
+            Some(target) => format!(" {} {target}", if self.1.unicode {"→"} else {"->"}),
+            None => "".into(),
+        };
+
+        // the leading 16 hex digits are plenty to tell files apart by eye.
+        let checksum = match self.1.checksum {
+            true => format!("{:<16}  ", self.0.checksum.as_deref().map_or("", |c| &c[..16])),
+            false => "".into(),
+        };
+        let inode = match self.1.inode {
+            true => format!("{:>12}  ", self.0.inode),
+            false => "".into(),
+        };
+
+        let columns = match self.1.flat {
+            true => format!("{bar}{size_str:>width$}  {inode}{checksum}", width = self.1.size_width),
+            false => {
+                let [created, modified, used] = self.1.dates(self.0);
+                format!("{bar}{size_str:>width$}  {created:>10}  {modified:>10}  {used:>10}  {inode}{checksum}", width = self.1.size_width)
+            },
+        };
+        let copy = match &self.0.duplicate_of {
+            Some(original) => format!(" (duplicate of {original})"),
+            None => "".into(),
+        };
+        let suffix = format!("{dir_mark}{link}{copy}");
+
+        // the path gives way first, so rows fit the line width whenever the columns do.
+        let fit = self.1.line_width.map(|w| w.saturating_sub(columns.chars().count() + suffix.chars().count()));
+        let ellipsis = ellipsis(self.1.unicode);
+        let tail = self.1.tail(&self.0.path);
+        let path = match self.1.path_width.into_iter().chain(fit).min() {
+            Some(width) => abbreviate(&tail, width, ellipsis),
+            None => tail.as_ref().into(),
+        };
+        let path = self.1.separators(path);
+
+        let row = format!("{columns}{path}{suffix}");
+        let color = self.1.path_colors.and_then(|theme| match (self.0.is_dir, self.0.is_symlink) {
+            (true, _) => Some(theme.dir),
+            (_, true) => Some(theme.symlink),
+            _ => None,
+        });
+        match (self.1.line_width, color) {
+            (Some(width), _) if row.chars().count() > width => {
+                let cut: String = row.chars().take(width.saturating_sub(ellipsis.chars().count())).collect();
+                write!(f, "{cut}{ellipsis}")
+            },
+            (_, Some(color)) => write!(f, "{columns}{}{suffix}", path.with(color)),
+            _ => write!(f, "{row}"),
+        }
+    }
+}
+
+/// The colors used on a terminal, per `--theme`.
+#[derive(Clone, Copy)]
+struct Theme {
+    heading: Color,
+    timing: Color,
+    dir: Color,
+    symlink: Color,
+}
+
+impl Theme {
+    const DARK: Theme = Theme { heading: Color::Yellow, timing: Color::Green, dir: Color::Blue, symlink: Color::Cyan };
+    const LIGHT: Theme = Theme { heading: Color::DarkBlue, timing: Color::DarkGreen, dir: Color::DarkBlue, symlink: Color::DarkCyan };
+    const PLAIN: Theme = Theme { heading: Color::Reset, timing: Color::Reset, dir: Color::Reset, symlink: Color::Reset };
+
+    fn new(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Self::DARK,
+            ThemeName::Light => Self::LIGHT,
+            ThemeName::Auto if light_background() => Self::LIGHT,
+            ThemeName::Auto => Self::DARK,
+        }
+    }
+}
+
+/// Whether $COLORFGBG, set by rxvt, Konsole and others as "fg;bg", gives a light
+/// background color: 7 (white) or 9 to 15, bar 8 (dark grey).
+fn light_background() -> bool {
+    std::env::var("COLORFGBG").ok()
+        .and_then(|v| v.rsplit(';').next().and_then(|bg| bg.parse::<u8>().ok()))
+        .is_some_and(|bg| bg == 7 || (9..=15).contains(&bg))
+}
+
+/// How the listing is written, see `resolve_output_mode`.
+#[derive(Clone, Copy, PartialEq)]
+pub enum OutputMode {
+    /// Redrawn in place on the terminal as the scan runs.
+    Live,
+    /// Every column as plain lines, once the scan completes.
+    Table,
+    /// Only the size and path columns as plain lines, once the scan completes.
+    Flat,
+    Json { pretty: bool },
+    Msgpack,
+    Yaml,
+    SizeOnly,
+}
+
+/// Decides how the listing is written, the one place that checks for a terminal. A
+/// terminal gets the live table, anything else the size and path alone, unless a
+/// `--format` or `--borders` is given, which always wins.
+pub fn resolve_output_mode(args: &Args) -> OutputMode {
+    let terminal = stdout().is_terminal() && terminal::size().is_ok() && position().is_ok();
+    match args.format {
+        _ if args.size_only => OutputMode::SizeOnly,
+        OutputFormat::Json => OutputMode::Json { pretty: args.json_pretty },
+        OutputFormat::Msgpack => OutputMode::Msgpack,
+        OutputFormat::Yaml => OutputMode::Yaml,
+        OutputFormat::Table if terminal => OutputMode::Live,
+        OutputFormat::Table if args.format_given || args.borders => OutputMode::Table,
+        OutputFormat::Table => OutputMode::Flat,
     }
 }
 
-#[derive(Clone)]
 pub struct FilePrinter {
     max_line: u16,
     status_line: u16,
     start_line: i16,
     pub page_size: usize,
-    print_index: bool,
-    size_factor: f64,
+    index_width: Option<usize>,
+    format: RowFormat,
     flush_count: usize,
     status_count: usize,
+    status_bottom: bool,
+    mode: OutputMode,
+    display_sort: DisplaySort,
+    theme: Theme,
+    trim: usize,
+    display_min: u64,
+    rel_min: Option<f64>,
+    canonical: bool,
+    verify: bool,
+    find_dupes: bool,
+    reverse_final: bool,
+    borders: bool,
+    /// Size of the buffer plain output is written through, per `--output-buffer-size`.
+    buffer_size: usize,
+    /// Where entries are written as they arrive, with `--format msgpack`.
+    stream: Option<BufWriter<io::Stdout>>,
+    /// Messages for stderr held back until the terminal is restored.
+    notes: Vec<String>,
 }
 
 impl FilePrinter {
+    /// The least room `--no-scroll` needs below the cursor: the strap line, status,
+    /// heading and a few rows.
+    const NO_SCROLL_ROWS: u16 = 8;
+
     pub fn print_status(&mut self, msg: StatusMsg) {
+        if self.mode != OutputMode::Live {
+            return;
+        }
         self.status_count += 1;
         queue!(stdout(), MoveTo(0, self.status_line)).unwrap();
+        self.queue_status(msg);
+        if self.status_count.is_multiple_of(20) {
+            stdout().flush().unwrap();
+        }
+    }
 
+    /// Sends a redrawn status line to the terminal now, instead of with every 20th.
+    pub fn flush_status(&self) {
+        if self.mode == OutputMode::Live {
+            stdout().flush().unwrap();
+        }
+    }
+
+    fn queue_status(&self, msg: StatusMsg) {
         match msg {
             StatusMsg::Final(sr, elapsed_time) => {
                 queue!(
                     stdout(),
                     Print(Status(&sr)),
-                    Print("in "),
-                    SetForegroundColor(Color::Green),
+                    Print(" in "),
+                    SetForegroundColor(self.theme.timing),
                     Print(format!("{:.3}", elapsed_time.as_secs_f64())),
                     ResetColor,
                     Print(" seconds"),
                 ).unwrap();
             },
-            StatusMsg::Status(sr) => queue!(stdout(), Print(Status(sr))).unwrap(),
-        }
-        if self.status_count % 20 == 0 {
-            stdout().flush().unwrap();
+            StatusMsg::Status(sr, floor) => {
+                let floor = match floor {
+                    0 => "".into(),
+                    _ => format!(" floor: {}", human_size(floor)),
+                };
+                queue!(stdout(), Print(Status(sr)), Print(floor), Clear(ClearType::UntilNewLine)).unwrap()
+            },
         }
     }
 
-    pub fn new(_strap_line: &str) -> Self {
-        let args = Args::parse_args();
+    pub fn new(args: &Args, mode: OutputMode) -> Self {
+        let strap_line = &args.title;
 
+        let base: f64 = if args.si {1000.0} else {1024.0};
         let mut size_factor: f64 = 1f64;
-        let mut size_heading: String = "Bytes".into();
+        let mut size_heading = "Bytes";
         if args.g_byt {
-            size_factor = 1024f64.powi(3);
-            size_heading = "Gb".into();
+            size_factor = base.powi(3);
+            size_heading = if args.si {"GB"} else {"GiB"};
         } else if args.m_byt {
-            size_factor = 1024f64.powi(2);
-            size_heading = "Mb".into();
+            size_factor = base.powi(2);
+            size_heading = if args.si {"MB"} else {"MiB"};
         };
 
-        let lpad = match args.index_print {
-            true => "    ",
-            false => "",
+        // wide enough for the last rank, never narrower than three digits.
+        let index_width = args.index_print.then(|| args.nentries.to_string().len().max(3));
+        let mut lpad = " ".repeat(index_width.map_or(0, |w| w + 1));
+        if args.bars {
+            lpad += &" ".repeat(RowFormat::BAR_WIDTH + 2);
+        }
+
+        // off with --no-color, or a non-empty $NO_COLOR as at no-color.org.
+        let colors = !args.no_color && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty());
+        let theme = match colors {
+            true => Theme::new(args.theme),
+            false => Theme::PLAIN,
+        };
+        let format = RowFormat {
+            size_factor,
+            size_heading,
+            size_width: args.size_width,
+            path_width: args.abbreviate_paths,
+            path_tail: args.path_tail,
+            bars: args.bars,
+            bar_max: 0,
+            unicode: !args.ascii,
+            inode: cfg!(unix) && args.show_inode,
+            checksum: args.checksum,
+            flat: mode == OutputMode::Flat,
+            forward_slashes: args.forward_slashes,
+            relative_to: args.relative_dates.then(SystemTime::now),
+            path_colors: (mode == OutputMode::Live && colors).then_some(theme),
+            line_width: args.max_line_width
+                .or_else(|| (mode == OutputMode::Live).then(terminal::size).and_then(|s| s.ok()).map(|s| s.0 as usize))
+                .map(|w| w.saturating_sub(index_width.map_or(0, |i| i + 1))),
+        };
+        let path_heading = match args.checksum {
+            true => format!("{:<18}path", "checksum"),
+            false => "path".into(),
+        };
+        let path_heading = match format.inode {
+            true => format!("{:>12}  {path_heading}", "inode"),
+            false => path_heading,
         };
 
+        // without a terminal to draw on, nothing is printed until the scan completes.
+        if mode != OutputMode::Live {
+            let table = matches!(mode, OutputMode::Table | OutputMode::Flat);
+            if table && !strap_line.is_empty() {
+                println!("{strap_line}");
+            }
+            if table && !args.borders {
+                match mode {
+                    OutputMode::Flat => println!("{lpad}{size_heading:>width$}  {path_heading}", width = args.size_width),
+                    _ => println!("{lpad}{size_heading:>width$}   created     modified    accessed     {path_heading}", width = args.size_width),
+                }
+            }
+            return Self {
+                max_line: 0,
+                status_line: 0,
+                start_line: 0,
+                page_size: 0,
+                index_width,
+                format,
+                flush_count: 0,
+                status_count: 0,
+                status_bottom: false,
+                mode,
+                display_sort: args.display_sort,
+                theme,
+                trim: args.trim_top,
+                display_min: args.display_min,
+                rel_min: args.rel_min,
+                canonical: args.canonical,
+                buffer_size: args.output_buffer_size,
+                verify: args.verify,
+                find_dupes: args.find_dupes,
+                reverse_final: args.reverse_final,
+                borders: args.borders,
+                stream: (mode == OutputMode::Msgpack).then(|| BufWriter::with_capacity(args.output_buffer_size, stdout())),
+                notes: vec![],
+            };
+        }
+
+        // the page is drawn from the top when too little of it is left below the cursor.
+        if args.no_scroll && terminal_rows().saturating_sub(position().map_or(0, |p| p.1)) < Self::NO_SCROLL_ROWS {
+            execute!(stdout(), MoveTo(0, 0), Clear(ClearType::FromCursorDown)).unwrap();
+        }
+        if !strap_line.is_empty() {
+            println!("{strap_line}");
+        }
         terminal::enable_raw_mode().expect("Failed to enable raw mode");
+        keep_interrupts();
+        match (args.status_bottom, args.no_scroll) {
+            (true, _) => {},
+            (false, false) => queue!(stdout(), ScrollUp(2)).unwrap(),
+            (false, true) => queue!(stdout(), Print("\n\n")).unwrap(),
+        }
+        let heading_style = match args.ascii {
+            true => "".to_string(),
+            false => format!("{}{}", Attribute::Italic, Attribute::Underdotted),
+        };
         execute!(
             stdout(),
-            ScrollUp(2),
-            SetForegroundColor(Color::Yellow),
+            SetForegroundColor(theme.heading),
             Print("\n"),
             Print(format!(
-                "{lpad}{}{}    {size_heading:>10}    created     modified    accessed     path",
-                Attribute::Italic,
-                Attribute::Underdotted,
+                "{lpad}{heading_style}{size_heading:>width$}   created     modified    accessed     {path_heading}",
+                width = args.size_width,
             )),
             SetAttribute(Attribute::Reset),
             Print("\n"),
             ResetColor
         ).unwrap();
 
-        let pos = position().unwrap().1 as i16;
+        let pos = position().map_or(0, |p| p.1);
+        let status_line = match args.status_bottom {
+            true => terminal_rows() - 1,
+            false => pos.saturating_sub(3),
+        };
+        // rows stop short of the reserved bottom line, below the cursor with --no-scroll,
+        // and otherwise as far up as the terminal can scroll them, keeping the heading
+        // and status above them on screen.
+        let above = match (args.no_scroll, args.status_bottom) {
+            (true, _) => pos,
+            (false, true) => 1,
+            (false, false) => pos - status_line,
+        };
+        let page_size = (terminal_rows() - args.status_bottom as u16).saturating_sub(above).clamp(1, 30) as usize;
+        let pos = pos as i16;
         Self {
             max_line: 0,
-            status_line: position().unwrap().1 - 3,
+            status_line,
             start_line: pos,
-            page_size: 30,
-            print_index: args.index_print,
-            size_factor,
+            page_size,
+            index_width,
+            format,
             flush_count: 0,
             status_count: 0,
+            status_bottom: args.status_bottom,
+            mode,
+            display_sort: args.display_sort,
+            theme,
+            trim: args.trim_top,
+            display_min: args.display_min,
+            rel_min: args.rel_min,
+            canonical: args.canonical,
+            buffer_size: args.output_buffer_size,
+            verify: args.verify,
+            find_dupes: args.find_dupes,
+            reverse_final: args.reverse_final,
+            borders: args.borders,
+            stream: None,
+            notes: vec![],
         }
     }
 
+    /// Prints the entry ranked `line_no`, counting the `--trim-top` entries, which
+    /// aren't shown.
     pub fn print_line(&mut self, entry: &Filesize, line_no: usize) {
-        if line_no < self.page_size {
+        let Some(line_no) = line_no.checked_sub(self.trim) else {
+            return;
+        };
+        if line_no < self.page_size && self.mode == OutputMode::Live && entry.size >= self.display_min {
             self.print( entry, line_no)
         }
     }
 
+    /// Sets the size that a full width bar represents.
+    pub fn scale_bars(&mut self, max: u64) {
+        self.format.bar_max = max;
+    }
+
     pub fn print_final(mut self, entries: ReverseSortedVec<Filesize>, status: StatusMsg) {
         let lines = self.page_size;
-        self.print_status(status);
+        if let Some(entry) = entries.first() {
+            self.scale_bars(entry.0.size);
+        }
+        let mut entries: Vec<Filesize> = entries.into_vec().into_iter()
+            .map(|e| e.0)
+            .filter(|e| e.size >= self.display_min)
+            .collect();
+        let shown = entries.len().min(lines);
+        if let Some(fraction) = self.rel_min {
+            let min = entries.first().map_or(0.0, |e| e.size as f64 * fraction);
+            entries.retain(|e| e.size as f64 >= min);
+        }
+        if self.verify {
+            verify(&mut entries);
+            if let Some(entry) = entries.first() {
+                self.scale_bars(entry.size);
+            }
+        }
+        if self.format.checksum {
+            for entry in entries.iter_mut().filter(|e| !e.is_dir) {
+                entry.checksum = checksum(entry.fs_path());
+            }
+        }
+        if self.find_dupes {
+            mark_duplicates(&mut entries);
+        }
+        match self.display_sort {
+            DisplaySort::Size => {},
+            DisplaySort::Path => entries.sort_by(|a, b| a.path.cmp(&b.path)),
+            DisplaySort::Created => entries.sort_by_key(|e| Reverse(e.times.created)),
+            DisplaySort::Modified => entries.sort_by_key(|e| Reverse(e.times.modified)),
+            DisplaySort::Accessed => entries.sort_by_key(|e| Reverse(e.times.accessed)),
+        }
+        let mut entries: Vec<&Filesize> = entries.iter().collect();
+        let duplicates = (self.format.checksum || self.find_dupes).then(|| Duplicates::find(&entries)).filter(|d| !d.is_empty());
+        match self.mode {
+            OutputMode::Json { pretty } => return print_json(&entries, status, pretty),
+            OutputMode::Msgpack => return self.finish_stream(status),
+            OutputMode::Yaml => return print_yaml(&entries, status),
+            OutputMode::SizeOnly => {
+                let mut out = BufWriter::with_capacity(self.buffer_size, stdout().lock());
+                for entry in entries {
+                    writeln!(out, "{}", entry.size).unwrap();
+                }
+                out.flush().unwrap();
+                return;
+            },
+            OutputMode::Live | OutputMode::Table | OutputMode::Flat => {},
+        }
+        if self.reverse_final {
+            entries.reverse();
+        }
+        if self.borders && self.mode == OutputMode::Live {
+            // the live rows and status give way to the grid, drawn from the top of them down.
+            let mut top = (self.start_line - 1).max(0) as u16;
+            if !self.status_bottom {
+                top = top.min(self.status_line);
+            }
+            execute!(stdout(), MoveTo(0, top), Clear(ClearType::FromCursorDown)).unwrap();
+            terminal::disable_raw_mode().expect("Failed to disable raw mode");
+            self.mode = OutputMode::Table;
+        }
+        if self.mode != OutputMode::Live {
+            self.print_plain(&entries, status);
+            if let Some(duplicates) = duplicates {
+                self.print_duplicates(&duplicates);
+            }
+            return;
+        }
+        // the visible lines were drawn in size order, before their checksums were known,
+        // their sizes verified, their paths resolved or the largest known.
+        if self.format.checksum || self.find_dupes || self.reverse_final || self.verify || self.canonical || self.rel_min.is_some()
+            || self.display_sort != DisplaySort::Size {
+            for (i, entry) in entries.iter().take(lines).enumerate() {
+                self.print(entry, i);
+            }
+            for row in entries.len().min(lines)..shown {
+                queue!(stdout(), MoveTo(0, row_on_screen(self.start_line, row)), Clear(ClearType::CurrentLine)).unwrap();
+            }
+        }
+        // a bottom status line is cleared, and the final status written below the full table.
+        let bottom_status = match self.status_bottom {
+            true => {
+                queue!(stdout(), MoveTo(0, self.status_line), Clear(ClearType::CurrentLine)).unwrap();
+                Some(status)
+            },
+            false => {
+                self.print_status(status);
+                None
+            },
+        };
 
         if entries.len() > lines {
             queue!(stdout(), MoveTo(0, self.max_line)).unwrap();
 
             for (i, entry) in entries.iter().skip(lines).enumerate() {
-                let ff = FileFormat(&entry.0, self.size_factor);
-                let (_, scrolls) = print(ff, lines + i, self.start_line, self.print_index, false);
-                queue!(stdout(), Print("\n")).unwrap();
-                self.status_line -= scrolls;
+                let ff = FileFormat(entry, &self.format);
+                let (_, scrolls) = print(ff, lines + i, self.start_line, self.index_width, 0, false);
+                queue!(stdout(), Print("\n"), MoveToColumn(0)).unwrap();
+                if !self.status_bottom {
+                    self.status_line = self.status_line.saturating_sub(scrolls);
+                }
             }
 
         } else {
             queue!(stdout(), MoveTo(0, self.max_line)).unwrap();
+            if self.status_bottom {
+                queue!(stdout(), Print("\n")).unwrap();
+            }
+        }
+        if let Some(status) = bottom_status {
+            queue!(stdout(), MoveToColumn(0)).unwrap();
+            self.queue_status(status);
+            queue!(stdout(), MoveToColumn(0)).unwrap();
         }
         execute!(stdout(), Print("\n\n")).unwrap();
         terminal::disable_raw_mode().expect("Failed to disable raw mode");
+        if let Some(duplicates) = duplicates {
+            self.print_duplicates(&duplicates);
+        }
+    }
+
+    /// Prints the groups of identical files, and with `--find-dupes` the space their
+    /// copies take.
+    fn print_duplicates(&self, duplicates: &Duplicates) {
+        print!("{duplicates}");
+        if self.find_dupes {
+            println!("reclaimable: {}", human_size(duplicates.reclaimable()));
+        }
     }
 
     fn print(&mut self, entry: &Filesize, line_no: usize) {
         self.flush_count += 1;
-        let ff = FileFormat(entry, self.size_factor);
-        let (_line_no, scrolls) = print(ff, line_no, self.start_line, self.print_index, self.flush_count % 2 ==0);
+        let ff = FileFormat(entry, &self.format);
+        let reserved = self.status_bottom as u16;
+        let (_line_no, scrolls) = print(ff, line_no, self.start_line, self.index_width, reserved, self.flush_count.is_multiple_of(2));
         self.max_line = _line_no.max(self.max_line);
         self.start_line -= scrolls as i16;
-        self.status_line -= scrolls;
+        if !self.status_bottom {
+            self.status_line = self.status_line.saturating_sub(scrolls);
+        }
+    }
+
+    /// Moves the cursor below the live rows, for a prompt to follow, when exiting early.
+    pub fn leave(&self) {
+        if self.mode == OutputMode::Live {
+            let _ = queue!(stdout(), MoveTo(0, self.max_line.max(self.status_line)), Print("\n\n"), MoveToColumn(0));
+        }
+    }
+
+    /// Prints `msg` to stderr, or holds it until the terminal is restored when stderr is
+    /// the terminal the live rows are drawn on, which it would otherwise tear through.
+    pub fn note(&mut self, msg: String) {
+        match self.mode == OutputMode::Live && io::stderr().is_terminal() {
+            true => self.notes.push(msg),
+            false => eprintln!("{msg}"),
+        }
+    }
+
+    /// Restores the terminal, then prints the notes held back while it was drawn on.
+    pub fn restore(&mut self) {
+        restore_terminal();
+        for msg in self.notes.drain(..) {
+            eprintln!("{msg}");
+        }
+    }
+
+    /// Writes an entry as it arrives, when entries are streamed as they are with
+    /// `--format msgpack`. Maps are self delimiting, so a reader can decode them one at a
+    /// time as they arrive.
+    pub fn stream(&mut self, entry: &Filesize) {
+        if entry.size < self.display_min {
+            return;
+        }
+        if let Some(out) = self.stream.as_mut() {
+            rmp_serde::encode::write_named(out, entry).unwrap();
+        }
+    }
+
+    /// Passes the streamed entries on, for a reader to decode while the scan runs.
+    pub fn flush_stream(&mut self) {
+        if let Some(out) = self.stream.as_mut() {
+            out.flush().unwrap();
+        }
+    }
+
+    /// Ends the stream of entries with the summary, as one more map.
+    fn finish_stream(&mut self, status: StatusMsg) {
+        let Some(mut out) = self.stream.take() else {
+            return;
+        };
+        if let StatusMsg::Final(sr, elapsed_time) = status {
+            let summary = Metrics { result: &sr, elapsed: elapsed_time.as_secs_f64() };
+            rmp_serde::encode::write_named(&mut out, &summary).unwrap();
+        }
+        out.flush().unwrap();
+    }
+
+    /// Prints the full table and summary as plain lines, without any cursor control.
+    fn print_plain(&self, entries: &[&Filesize], status: StatusMsg) {
+        let mut out = BufWriter::with_capacity(self.buffer_size, stdout().lock());
+        out.write_all(self.render(entries).as_bytes()).unwrap();
+        if let StatusMsg::Final(sr, elapsed_time) = status {
+            writeln!(out, "{}", summary(&sr, elapsed_time)).unwrap();
+        }
+        out.flush().unwrap();
+    }
+
+    /// The rows of the final table, as `render_rows` renders them with this printer's columns.
+    pub fn render(&self, entries: &[&Filesize]) -> String {
+        render_rows(entries, &self.format, self.index_width, self.borders)
+    }
+}
+
+
+/// Renders entries as the rows of the final table, one per line, with no cursor
+/// control, so they can be written anywhere. The heading isn't included, and rows are
+/// ranked from 1 in a column `index_width` wide, if given.
+fn render_rows(entries: &[&Filesize], format: &RowFormat, index_width: Option<usize>, borders: bool) -> String {
+    let mut rows = String::new();
+    match borders {
+        true => writeln!(rows, "{}", table(entries, format, index_width)).unwrap(),
+        false => for (i, entry) in entries.iter().enumerate() {
+            let index = index(index_width, i);
+            writeln!(rows, "{index}{}", FileFormat(entry, format)).unwrap();
+        },
+    }
+    rows
+}
+
+/// The listing as a grid for `--borders`, with the same columns as the plain rows.
+fn table(entries: &[&Filesize], format: &RowFormat, index_width: Option<usize>) -> Table {
+    let mut table = Table::new();
+    table.load_preset(if format.unicode {presets::UTF8_FULL_CONDENSED} else {presets::ASCII_FULL_CONDENSED});
+    if let Some(width) = format.line_width {
+        table.set_content_arrangement(ContentArrangement::Dynamic).set_width(width as u16);
+    }
+
+    let mut heading = vec![];
+    if index_width.is_some() {
+        heading.push("#");
+    }
+    if format.bars {
+        heading.push("");
+    }
+    heading.extend([format.size_heading, "created", "modified", "accessed"]);
+    if format.inode {
+        heading.push("inode");
+    }
+    if format.checksum {
+        heading.push("checksum");
+    }
+    heading.push("path");
+    table.set_header(heading);
+
+    let arrow = if format.unicode {"→"} else {"->"};
+    for (i, entry) in entries.iter().enumerate() {
+        let mut row = vec![];
+        if index_width.is_some() {
+            row.push((i + 1).to_string());
+        }
+        if format.bars {
+            row.push(format.bar(entry.size).trim_end().to_string());
+        }
+        row.push(format.size(entry.size));
+        row.extend(format.dates(entry));
+        if format.inode {
+            row.push(entry.inode.to_string());
+        }
+        if format.checksum {
+            row.push(entry.checksum.as_deref().map_or("", |c| &c[..16]).to_string());
+        }
+        let tail = format.tail(&entry.path);
+        let path = match format.path_width {
+            Some(width) => abbreviate(&tail, width, ellipsis(format.unicode)),
+            None => tail.as_ref().into(),
+        };
+        let path = format.separators(path);
+        let link = entry.link_target.as_ref().map_or("".into(), |t| format!(" {arrow} {t}"));
+        let copy = entry.duplicate_of.as_ref().map_or("".into(), |o| format!(" (duplicate of {o})"));
+        row.push(format!("{path}{}{link}{copy}", if entry.is_dir {"/"} else {""}));
+        table.add_row(row);
+    }
+
+    let size = index_width.is_some() as usize + format.bars as usize;
+    if let Some(column) = table.column_mut(size) {
+        column.set_cell_alignment(CellAlignment::Right);
+    }
+    if let Some(column) = table.column_mut(0).filter(|_| index_width.is_some()) {
+        column.set_cell_alignment(CellAlignment::Right);
+    }
+    table
+}
+
+
+/// Guards every way out of the printer that unwinds: whatever was queued reaches the
+/// terminal, and raw mode is never left enabled. `process::exit`, signals and the
+/// panic message itself skip this, and call `restore_terminal` instead.
+impl Drop for FilePrinter {
+    fn drop(&mut self) {
+        self.restore();
+    }
+}
+
+/// Flushes whatever was queued for the terminal and leaves raw mode, if it was entered.
+pub fn restore_terminal() {
+    let _ = stdout().flush();
+    if terminal::is_raw_mode_enabled().unwrap_or(false) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Turns Ctrl-C back into SIGINT, which raw mode otherwise reads as a key that nothing
+/// is waiting for, so a live scan can still be stopped.
+#[cfg(unix)]
+fn keep_interrupts() {
+    use std::os::fd::AsRawFd;
+    let Ok(tty) = std::fs::File::open("/dev/tty") else {
+        return;
+    };
+    // SAFETY: the termios struct is only read and written by the calls it's passed to.
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(tty.as_raw_fd(), &mut termios) == 0 {
+            termios.c_lflag |= libc::ISIG;
+            libc::tcsetattr(tty.as_raw_fd(), libc::TCSANOW, &termios);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn keep_interrupts() {}
+
+
+/// Re-reads the listed files for `--verify`, dropping any that have gone and taking
+/// the current size of the rest, then restores size order. Symlinks and
+/// directories are only checked to still exist.
+fn verify(entries: &mut Vec<Filesize>) {
+    entries.retain_mut(|entry| {
+        match entry.fs_path().symlink_metadata() {
+            Ok(meta) if meta.is_file() && entry.link_target.is_none() => {
+                entry.size = meta.len();
+                entry.modified = display_time(meta.modified());
+                entry.times.modified = meta.modified().ok();
+                true
+            },
+            Ok(_) => true,
+            Err(_) => false,
+        }
+    });
+    entries.sort_by(|a, b| b.cmp(a));
+}
+
+/// The listing and summary, as written by `--format json` and `--format yaml`.
+#[derive(Serialize)]
+struct Report<'a> {
+    entries: &'a [&'a Filesize],
+    summary: Metrics<'a>,
+    /// The machine scanned, so `--merge` can tell dumps apart.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    host: String,
+}
+
+impl<'a> Report<'a> {
+    fn new(entries: &'a [&'a Filesize], result: &'a ScanResult, elapsed: Duration) -> Self {
+        Report { entries, summary: Metrics { result, elapsed: elapsed.as_secs_f64() }, host: merge::hostname() }
+    }
+}
+
+/// Writes the listing and summary as one JSON object. Keys are written in a fixed
+/// order, see the README.
+fn print_json(entries: &[&Filesize], status: StatusMsg, pretty: bool) {
+    if let StatusMsg::Final(sr, elapsed_time) = status {
+        let report = Report::new(entries, &sr, elapsed_time);
+        let json = match pretty {
+            true => serde_json::to_string_pretty(&report),
+            false => serde_json::to_string(&report),
+        };
+        println!("{}", json.unwrap());
+    }
+}
+
+/// Writes the listing and summary to `path` as one JSON object, for `--output`.
+pub fn write_json(path: &Path, entries: &[&Filesize], result: &ScanResult, elapsed: Duration) -> Result<(), String> {
+    let report = Report::new(entries, result, elapsed);
+    let error = |e: &dyn Display| format!("cannot write {}: {e}", path.display());
+    let mut out = BufWriter::new(std::fs::File::create(path).map_err(|e| error(&e))?);
+    serde_json::to_writer(&mut out, &report).map_err(|e| error(&e))?;
+    out.flush().map_err(|e| error(&e))
+}
+
+/// Writes the listing and summary as a single YAML document, a map of the `entries`
+/// list and the `summary` map, with the same keys as JSON.
+fn print_yaml(entries: &[&Filesize], status: StatusMsg) {
+    if let StatusMsg::Final(sr, elapsed_time) = status {
+        let report = Report::new(entries, &sr, elapsed_time);
+        print!("{}", serde_yaml::to_string(&report).unwrap());
+    }
+}
+
+
+/// The final status line of plain output.
+fn summary(result: &ScanResult, elapsed: Duration) -> String {
+    format!("{} in {:.3} seconds", Status(result), elapsed.as_secs_f64())
+}
+
+
+/// The terminal height, or an assumed height when it can't be determined.
+fn terminal_rows() -> u16 {
+    const ASSUMED_ROWS: u16 = 24;
+    terminal::size().map_or(ASSUMED_ROWS, |s| s.1)
+}
+
+
+/// The marker left where `abbreviate` cut a path.
+fn ellipsis(unicode: bool) -> &'static str {
+    match unicode {
+        true => "…",
+        false => "...",
+    }
+}
+
+/// Shortens a path to at most `width` characters by replacing the middle with `ellipsis`,
+/// keeping the file name and as much of the leading path as fits. A name too long to
+/// fit keeps its end, and a width too narrow for the ellipsis gets as much of it as fits.
+fn abbreviate<'a>(path: &'a str, width: usize, ellipsis: &str) -> Cow<'a, str> {
+    let len = path.chars().count();
+    if len <= width {
+        return path.into();
+    }
+    let Some(room) = width.checked_sub(ellipsis.chars().count()) else {
+        return ellipsis.chars().take(width).collect::<String>().into();
+    };
+    let name_start = path.rfind(MAIN_SEPARATOR).unwrap_or(0);
+    let name = &path[name_start..];
+    let name_len = name.chars().count();
+    if name_len > room {
+        let end: String = name.chars().skip(name_len - room).collect();
+        return format!("{ellipsis}{end}").into();
+    }
+    let head: String = path.chars().take(room - name_len).collect();
+    format!("{head}{ellipsis}{name}").into()
+}
+
+
+static SI_UNITS: AtomicBool = AtomicBool::new(false);
+
+/// Switches `human_size` to 1000 based SI units, for `--si`.
+pub fn use_si_units() {
+    SI_UNITS.store(true, Relaxed);
+}
+
+/// Formats a byte count with a 1024 based IEC unit, e.g. "12.3 MiB", or under `--si`
+/// a 1000 based one, e.g. "12.9 MB".
+pub fn human_size(bytes: u64) -> String {
+    format_size(bytes, SI_UNITS.load(Relaxed))
+}
+
+fn format_size(bytes: u64, si: bool) -> String {
+    let (base, units) = match si {
+        true => (1000.0, ["B", "kB", "MB", "GB", "TB", "PB"]),
+        false => (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB"]),
+    };
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= base && unit < units.len() - 1 {
+        size /= base;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", units[unit]),
     }
 }
 
@@ -169,13 +1003,46 @@ pub fn display_time(sys_time: io::Result<SystemTime>) -> String {
         let datetime: DateTime<Utc> = t.into();
         datetime.format("%Y-%m-%d").to_string()
     } else {
-        return "-".into()
+        "-".into()
+    }
+}
+
+/// How long before `now` a time was, in its largest whole unit, e.g. '3d ago'.
+fn relative_time(time: Option<SystemTime>, now: SystemTime) -> String {
+    const UNITS: [(u64, &str); 6] = [(365 * 86400, "y"), (30 * 86400, "mo"), (86400, "d"), (3600, "h"), (60, "m"), (1, "s")];
+    let Some(time) = time else {
+        return "-".into();
+    };
+    let (secs, future) = match now.duration_since(time) {
+        Ok(ago) => (ago.as_secs(), false),
+        Err(e) => (e.duration().as_secs(), true),
+    };
+    let (unit, name) = UNITS.iter().copied().find(|(unit, _)| secs >= *unit).unwrap_or((1, "s"));
+    match future {
+        true => format!("in {}{name}", secs / unit),
+        false => format!("{}{name} ago", secs / unit),
     }
 }
 
-fn print(entry: FileFormat, line_no: usize, start_line: i16, print_index: bool, flush: bool) -> (u16, u16) {
-    let mut _line_no = (start_line + line_no as i16) as u16;
-    let terminal_end = terminal::size().unwrap().1;
+/// The rank column for the entry at `line_no`, empty without `--index-print`.
+fn index(width: Option<usize>, line_no: usize) -> String {
+    match width {
+        Some(width) => format!("{:>width$} ", line_no + 1),
+        None => "".into(),
+    }
+}
+
+/// The terminal row of row `line_no` of a page starting at `start_line`, which may have
+/// scrolled above the top of the terminal.
+fn row_on_screen(start_line: i16, line_no: usize) -> u16 {
+    (start_line as i64 + line_no as i64).clamp(0, u16::MAX as i64) as u16
+}
+
+/// Prints an entry at `start_line + line_no`, scrolling the terminal when that falls
+/// off the end. `reserved` rows at the bottom of the terminal are never printed on.
+fn print(entry: FileFormat, line_no: usize, start_line: i16, index_width: Option<usize>, reserved: u16, flush: bool) -> (u16, u16) {
+    let mut _line_no = row_on_screen(start_line, line_no);
+    let terminal_end = terminal_rows() - reserved;
     let mut scrolls: u16 = 0;
 
     if _line_no == terminal_end {
@@ -191,7 +1058,7 @@ fn print(entry: FileFormat, line_no: usize, start_line: i16, print_index: bool,
     queue!(
                 stdout(),
                 MoveTo(0, _line_no),
-                Print(if print_index {format!("{:>3} ", line_no + 1)}  else {"".into()}),
+                Print(index(index_width, line_no)),
                 Print(entry),
                 Clear(ClearType::UntilNewLine),
             )
@@ -200,4 +1067,127 @@ fn print(entry: FileFormat, line_no: usize, start_line: i16, print_index: bool,
         stdout().flush().unwrap();
     }
     (_line_no, scrolls)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::clock::{Clock, FixedClock};
+    use std::path::MAIN_SEPARATOR_STR;
+
+    #[test]
+    fn summary_reports_the_clock_elapsed_time() {
+        let clock = FixedClock::default();
+        clock.advance(Duration::from_millis(1500));
+        let result = ScanResult { files: 1234, directories: 5, ..ScanResult::default() };
+        assert_eq!(summary(&result, clock.elapsed()), "scanned files: 1,234 directories: 5 in 1.500 seconds");
+    }
+
+    fn format() -> RowFormat {
+        RowFormat {
+            size_factor: 1.0,
+            size_heading: "Bytes",
+            size_width: 15,
+            path_width: None,
+            path_tail: None,
+            bars: false,
+            bar_max: 0,
+            unicode: true,
+            inode: false,
+            checksum: false,
+            flat: false,
+            forward_slashes: false,
+            relative_to: None,
+            path_colors: None,
+            line_width: None,
+        }
+    }
+
+    fn entry(path: &str, size: u64) -> Filesize {
+        Filesize {
+            path: path.into(),
+            fs_path: path.into(),
+            size,
+            created: "2026-01-02".into(),
+            modified: "2026-01-03".into(),
+            used: "2026-01-04".into(),
+            is_dir: false,
+            link_target: None,
+            is_symlink: false,
+            times: Default::default(),
+            device: 0,
+            inode: 42,
+            checksum: None,
+            duplicate_of: None,
+        }
+    }
+
+    #[test]
+    fn render_rows_writes_every_column() {
+        let (big, small) = (entry("data/big.bin", 1_234_567), entry("data/small.txt", 12));
+        assert_eq!(render_rows(&[&big, &small], &format(), None, false), concat!(
+            "      1,234,567  2026-01-02  2026-01-03  2026-01-04  data/big.bin\n",
+            "             12  2026-01-02  2026-01-03  2026-01-04  data/small.txt\n",
+        ));
+    }
+
+    #[test]
+    fn render_rows_ranks_and_marks_entries() {
+        let dir = Filesize { is_dir: true, ..entry("data", 4096) };
+        let link = Filesize { link_target: Some("/srv/big.bin".into()), ..entry("big", 10) };
+        let format = RowFormat { flat: true, inode: true, ..format() };
+        assert_eq!(render_rows(&[&dir, &link], &format, Some(3), false), concat!(
+            "  1           4,096            42  data/\n",
+            "  2              10            42  big → /srv/big.bin\n",
+        ));
+    }
+
+    #[test]
+    fn render_rows_fits_the_line_width() {
+        let long = entry("data/a/rather/long/path/to/big.bin", 1024);
+        let format = RowFormat { flat: true, line_width: Some(30), ..format() };
+        assert_eq!(render_rows(&[&long], &format, None, false), "          1,024  data…/big.bin\n");
+    }
+
+    #[test]
+    fn abbreviate_keeps_the_name_and_leading_path() {
+        let path = ["", "home", "user", "projects", "data.csv"].join(MAIN_SEPARATOR_STR);
+        let name = format!("{MAIN_SEPARATOR}data.csv");
+        assert_eq!(abbreviate(&path, 40, "…"), path);
+        assert_eq!(abbreviate(&path, 16, "…"), format!("{}…{name}", &path[..6]));
+        assert_eq!(abbreviate(&path, 16, "..."), format!("{}...{name}", &path[..4]));
+    }
+
+    #[test]
+    fn abbreviate_never_exceeds_the_width() {
+        let path = ["", "home", "user", "a_rather_long_file_name.txt"].join(MAIN_SEPARATOR_STR);
+        for ellipsis in ["…", "..."] {
+            for width in 0..=path.len() {
+                let short = abbreviate(&path, width, ellipsis);
+                assert!(short.chars().count() <= width, "{short:?} is wider than {width}");
+            }
+        }
+        assert_eq!(abbreviate(&path, 0, "…"), "");
+        assert_eq!(abbreviate(&path, 1, "…"), "…");
+        assert_eq!(abbreviate(&path, 2, "…"), "…t");
+        assert_eq!(abbreviate(&path, 3, "…"), "…xt");
+        assert_eq!(abbreviate(&path, 2, "..."), "..");
+        assert_eq!(abbreviate(&path, 3, "..."), "...");
+    }
+
+    #[test]
+    fn human_size_labels_1024_based_units() {
+        assert_eq!(format_size(1023, false), "1023 B");
+        assert_eq!(format_size(1024, false), "1.0 KiB");
+        assert_eq!(format_size(12_900_000, false), "12.3 MiB");
+        assert_eq!(format_size(3 << 30, false), "3.0 GiB");
+    }
+
+    #[test]
+    fn human_size_labels_1000_based_units_under_si() {
+        assert_eq!(format_size(999, true), "999 B");
+        assert_eq!(format_size(1000, true), "1.0 kB");
+        assert_eq!(format_size(12_900_000, true), "12.9 MB");
+        assert_eq!(format_size(3 << 30, true), "3.2 GB");
+    }
+}
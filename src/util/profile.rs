@@ -0,0 +1,64 @@
+use std::sync::atomic::{AtomicBool, AtomicU64};
+use std::sync::atomic::Ordering::Relaxed;
+use std::future::Future;
+use std::time::Instant;
+
+
+/// The scan phases timed under `--profile`.
+#[derive(Clone, Copy)]
+pub enum Phase {
+    ReadDir,
+    Metadata,
+    Send,
+    Render,
+}
+
+const PHASES: [(Phase, &str); 4] = [
+    (Phase::ReadDir, "read_dir"),
+    (Phase::Metadata, "metadata"),
+    (Phase::Send, "channel sends"),
+    (Phase::Render, "rendering"),
+];
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static NANOS: [AtomicU64; 4] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+
+pub fn enable() {
+    ENABLED.store(true, Relaxed);
+}
+
+/// Runs `f`, adding its duration to the phase total when profiling is enabled.
+pub fn time<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    if !ENABLED.load(Relaxed) {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    NANOS[phase as usize].fetch_add(start.elapsed().as_nanos() as u64, Relaxed);
+    result
+}
+
+/// Awaits `f`, adding the time until it completes to the phase total when profiling
+/// is enabled.
+pub async fn time_async<T>(phase: Phase, f: impl Future<Output = T>) -> T {
+    if !ENABLED.load(Relaxed) {
+        return f.await;
+    }
+    let start = Instant::now();
+    let result = f.await;
+    NANOS[phase as usize].fetch_add(start.elapsed().as_nanos() as u64, Relaxed);
+    result
+}
+
+/// Prints the cumulative time per phase to stderr. Scan phases are summed across
+/// all tasks, so can add up to more than the elapsed time.
+pub fn report() {
+    if !ENABLED.load(Relaxed) {
+        return;
+    }
+    eprintln!("profile (cumulative seconds):");
+    for (phase, name) in PHASES {
+        let secs = NANOS[phase as usize].load(Relaxed) as f64 / 1e9;
+        eprintln!("  {name:<14}{secs:>10.3}");
+    }
+}
@@ -0,0 +1,24 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::args::Args;
+
+
+/// When the last run recorded in the `--state` file started, none before the first run.
+pub fn last_run(path: &Path) -> Option<SystemTime> {
+    let state = match fs::read_to_string(path) {
+        Ok(state) => state,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => Args::exit_invalid(&format!("cannot read {}: {e}", path.display())),
+    };
+    let secs: u64 = state.trim().parse()
+        .unwrap_or_else(|_| Args::exit_invalid(&format!("{} is not a scanr state file", path.display())));
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// Records `started` in the `--state` file as seconds since the epoch, for the next run.
+pub fn record(path: &Path, started: SystemTime) -> Result<(), String> {
+    let secs = started.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    fs::write(path, format!("{secs}\n")).map_err(|e| format!("cannot write {}: {e}", path.display()))
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+
+
+/// The files directly in each directory scanned, keyed by the directory's path, for
+/// `--tree-json`. Nested into a tree once the scan completes.
+#[derive(Default)]
+pub struct Tree {
+    dirs: HashMap<PathBuf, Vec<(String, u64)>>,
+}
+
+#[derive(Serialize)]
+struct Node {
+    name: String,
+    size: u64,
+    files: Vec<Leaf>,
+    dirs: Vec<Node>,
+}
+
+#[derive(Serialize)]
+struct Leaf {
+    name: String,
+    size: u64,
+}
+
+impl Tree {
+    pub fn add(&mut self, dir: PathBuf, files: Vec<(String, u64)>) {
+        self.dirs.entry(dir).or_default().extend(files);
+    }
+
+    /// Writes the tree below `root` to `path` as nested JSON objects, each directory
+    /// with its total size, and its files and subdirectories largest first.
+    pub fn write(mut self, path: &Path, root: &Path) -> Result<(), String> {
+        let mut children: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+        for dir in self.dirs.keys().filter(|d| d.as_path() != root) {
+            if let Some(parent) = dir.parent() {
+                children.entry(parent.to_path_buf()).or_default().push(dir.clone());
+            }
+        }
+        let tree = self.node(root, &mut children);
+
+        let error = |e: &dyn std::fmt::Display| format!("cannot write {}: {e}", path.display());
+        let mut out = BufWriter::new(File::create(path).map_err(|e| error(&e))?);
+        serde_json::to_writer(&mut out, &tree).map_err(|e| error(&e))?;
+        out.flush().map_err(|e| error(&e))
+    }
+
+    fn node(&mut self, dir: &Path, children: &mut HashMap<PathBuf, Vec<PathBuf>>) -> Node {
+        let mut files: Vec<Leaf> = self.dirs.remove(dir).unwrap_or_default().into_iter()
+            .map(|(name, size)| Leaf { name, size })
+            .collect();
+        files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        let mut dirs: Vec<Node> = children.remove(dir).unwrap_or_default().iter()
+            .map(|child| self.node(child, children))
+            .collect();
+        dirs.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+        let size = files.iter().map(|f| f.size).sum::<u64>() + dirs.iter().map(|d| d.size).sum::<u64>();
+        let name = dir.file_name().map_or_else(|| dir.to_string_lossy(), |n| n.to_string_lossy()).into_owned();
+        Node { name, size, files, dirs }
+    }
+}
@@ -0,0 +1,86 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use num_format::{Locale, ToFormattedString};
+use crate::Filesize;
+use crate::util::filter::unescape_mount;
+use crate::util::print::human_size;
+
+
+/// Files split by the filesystem they're on, for `--by-volume`, each with its own
+/// top n and subtotals.
+pub struct Volumes {
+    n: usize,
+    volumes: HashMap<u64, Volume>,
+}
+
+#[derive(Default)]
+struct Volume {
+    files: usize,
+    bytes: u64,
+    top: BinaryHeap<Reverse<Filesize>>,
+}
+
+impl Volumes {
+    pub fn new(n: usize) -> Self {
+        Volumes { n, volumes: HashMap::new() }
+    }
+
+    pub fn add(&mut self, file: &Filesize) {
+        if file.is_dir {
+            return;
+        }
+        let volume = self.volumes.entry(file.device).or_default();
+        volume.files += 1;
+        volume.bytes += file.size;
+        if volume.top.len() < self.n {
+            volume.top.push(Reverse(file.clone()));
+        } else if volume.top.peek().is_some_and(|smallest| file.size > smallest.0.size) {
+            volume.top.pop();
+            volume.top.push(Reverse(file.clone()));
+        }
+    }
+}
+
+impl Display for Volumes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let names = mount_names();
+        let mut volumes: Vec<_> = self.volumes.iter().collect();
+        volumes.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then_with(|| a.0.cmp(b.0)));
+
+        writeln!(f, "by volume:")?;
+        for (device, volume) in &volumes {
+            let name = names.get(device).map_or_else(|| format!("device {device}"), |m| m.clone());
+            writeln!(f, "{name}: {} files, {}", volume.files.to_formatted_string(&Locale::en), human_size(volume.bytes))?;
+            let mut top: Vec<_> = volume.top.iter().map(|r| &r.0).collect();
+            top.sort_by(|a, b| b.cmp(a));
+            for file in top {
+                writeln!(f, "  {:>10}  {}", human_size(file.size), file.path)?;
+            }
+        }
+        let files: usize = volumes.iter().map(|v| v.1.files).sum();
+        let bytes: u64 = volumes.iter().map(|v| v.1.bytes).sum();
+        writeln!(f, "total: {} files, {}", files.to_formatted_string(&Locale::en), human_size(bytes))
+    }
+}
+
+/// The mount point of each device in /proc/mounts, the first listed where a device
+/// is mounted more than once. Empty where /proc/mounts doesn't exist.
+#[cfg(unix)]
+fn mount_names() -> HashMap<u64, String> {
+    use std::os::unix::fs::MetadataExt;
+    let mut names = HashMap::new();
+    for mount in fs::read_to_string("/proc/mounts").unwrap_or_default().lines()
+        .filter_map(|line| line.split_whitespace().nth(1)).map(unescape_mount) {
+        if let Ok(meta) = fs::metadata(&mount) {
+            names.entry(meta.dev()).or_insert(mount);
+        }
+    }
+    names
+}
+
+#[cfg(not(unix))]
+fn mount_names() -> HashMap<u64, String> {
+    HashMap::new()
+}